@@ -5,7 +5,7 @@
 //! [HintWriter]: kona_preimage::HintWriter
 
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
 use async_trait::async_trait;
 use core::num::NonZeroUsize;
 use kona_preimage::{HintWriterClient, PreimageKey, PreimageOracleClient};
@@ -82,6 +82,56 @@ where
             Ok(())
         }
     }
+
+    async fn get_chunked<F>(
+        &self,
+        key: PreimageKey,
+        buf: &mut [u8],
+        mut on_chunk: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&[u8]) -> Result<()> + Send,
+    {
+        ensure!(!buf.is_empty(), "Chunk buffer must not be empty");
+
+        if let Some(value) = self.cache.lock().get(&key) {
+            for chunk in value.chunks(buf.len()) {
+                on_chunk(chunk)?;
+            }
+            return Ok(value.len());
+        }
+
+        // Streamed preimages are not cached, as that would require holding them in memory in full.
+        self.oracle_reader.get_chunked(key, buf, on_chunk).await
+    }
+
+    async fn get_batch(&self, keys: &[PreimageKey]) -> Result<Vec<Vec<u8>>> {
+        // Serve what is already cached, and fetch the remaining preimages in a single batch.
+        let mut preimages = {
+            let mut cache_lock = self.cache.lock();
+            keys.iter().map(|key| cache_lock.get(key).cloned()).collect::<Vec<_>>()
+        };
+        let missing = keys
+            .iter()
+            .zip(preimages.iter())
+            .filter_map(|(key, preimage)| preimage.is_none().then_some(*key))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(preimages.into_iter().flatten().collect());
+        }
+
+        let mut fetched = self.oracle_reader.get_batch(&missing).await?.into_iter();
+        let mut cache_lock = self.cache.lock();
+        for (key, preimage) in keys.iter().zip(preimages.iter_mut()) {
+            if preimage.is_none() {
+                let value = fetched.next().ok_or_else(|| anyhow!("Missing preimage in batch"))?;
+                cache_lock.put(*key, value.clone());
+                *preimage = Some(value);
+            }
+        }
+
+        Ok(preimages.into_iter().flatten().collect())
+    }
 }
 
 #[async_trait]
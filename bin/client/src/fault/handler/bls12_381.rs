@@ -0,0 +1,280 @@
+//! Contains the accelerated versions of the BLS12-381 precompiles, as specified in EIP-2537.
+//!
+//! The gas cost of each call is computed, and its input length validated, before the call is
+//! delegated to the host. The validity of the points within the input is checked by the host.
+
+use super::fpvm_precompile_call;
+use alloc::format;
+use alloy_primitives::{Address, Bytes};
+use revm::{
+    precompile::{u64_to_address, Error as PrecompileError, PrecompileWithAddress},
+    primitives::{Precompile, PrecompileOutput, PrecompileResult},
+};
+
+const G1_ADD_ADDRESS: Address = u64_to_address(0x0b);
+const G1_MUL_ADDRESS: Address = u64_to_address(0x0c);
+const G1_MSM_ADDRESS: Address = u64_to_address(0x0d);
+const G2_ADD_ADDRESS: Address = u64_to_address(0x0e);
+const G2_MUL_ADDRESS: Address = u64_to_address(0x0f);
+const G2_MSM_ADDRESS: Address = u64_to_address(0x10);
+const PAIRING_ADDRESS: Address = u64_to_address(0x11);
+const MAP_FP_TO_G1_ADDRESS: Address = u64_to_address(0x12);
+const MAP_FP2_TO_G2_ADDRESS: Address = u64_to_address(0x13);
+
+// The addresses, gas costs and input lengths below are those of the `bls12_381` precompiles of
+// `revm-precompile`, which the host executes the delegated calls with. `revm` only exposes them
+// with its `blst` feature, which the client program cannot be built with, so the tests below check
+// that the client charges the same gas and accepts the same inputs as `revm`.
+const G1_ADD_GAS: u64 = 500;
+const G1_ADD_INPUT_LEN: usize = 256;
+const G1_MUL_GAS: u64 = 12_000;
+const G1_MUL_INPUT_LEN: usize = 160;
+const G2_ADD_GAS: u64 = 800;
+const G2_ADD_INPUT_LEN: usize = 512;
+const G2_MUL_GAS: u64 = 45_000;
+const G2_MUL_INPUT_LEN: usize = 288;
+const PAIRING_PER_PAIR_GAS: u64 = 43_000;
+const PAIRING_BASE_GAS: u64 = 65_000;
+const PAIRING_INPUT_LEN: usize = 384;
+const MAP_FP_TO_G1_GAS: u64 = 5_500;
+const MAP_FP_TO_G1_INPUT_LEN: usize = 64;
+const MAP_FP2_TO_G2_GAS: u64 = 75_000;
+const MAP_FP2_TO_G2_INPUT_LEN: usize = 128;
+
+/// The divisor of the multi-scalar-multiplication discounts.
+const MSM_MULTIPLIER: u64 = 1000;
+
+/// The multi-scalar-multiplication discounts, indexed by the number of pairs minus one.
+const MSM_DISCOUNT_TABLE: [u16; 128] = [
+    1200, 888, 764, 641, 594, 547, 500, 453, 438, 423, 408, 394, 379, 364, 349, 334, 330, 326, 322,
+    318, 314, 310, 306, 302, 298, 294, 289, 285, 281, 277, 273, 269, 268, 266, 265, 263, 262, 260,
+    259, 257, 256, 254, 253, 251, 250, 248, 247, 245, 244, 242, 241, 239, 238, 236, 235, 233, 232,
+    231, 229, 228, 226, 225, 223, 222, 221, 220, 219, 219, 218, 217, 216, 216, 215, 214, 213, 213,
+    212, 211, 211, 210, 209, 208, 208, 207, 206, 205, 205, 204, 203, 202, 202, 201, 200, 199, 199,
+    198, 197, 196, 196, 195, 194, 193, 193, 192, 191, 191, 190, 189, 188, 188, 187, 186, 185, 185,
+    184, 183, 182, 182, 181, 180, 179, 179, 178, 177, 176, 176, 175, 174,
+];
+
+/// The FPVM-accelerated BLS12-381 precompiles.
+pub(crate) const FPVM_BLS12_381_PRECOMPILES: [PrecompileWithAddress; 9] = [
+    PrecompileWithAddress(G1_ADD_ADDRESS, Precompile::Standard(fpvm_g1_add)),
+    PrecompileWithAddress(G1_MUL_ADDRESS, Precompile::Standard(fpvm_g1_mul)),
+    PrecompileWithAddress(G1_MSM_ADDRESS, Precompile::Standard(fpvm_g1_msm)),
+    PrecompileWithAddress(G2_ADD_ADDRESS, Precompile::Standard(fpvm_g2_add)),
+    PrecompileWithAddress(G2_MUL_ADDRESS, Precompile::Standard(fpvm_g2_mul)),
+    PrecompileWithAddress(G2_MSM_ADDRESS, Precompile::Standard(fpvm_g2_msm)),
+    PrecompileWithAddress(PAIRING_ADDRESS, Precompile::Standard(fpvm_pairing)),
+    PrecompileWithAddress(MAP_FP_TO_G1_ADDRESS, Precompile::Standard(fpvm_map_fp_to_g1)),
+    PrecompileWithAddress(MAP_FP2_TO_G2_ADDRESS, Precompile::Standard(fpvm_map_fp2_to_g2)),
+];
+
+/// Performs an FPVM-accelerated BLS12-381 `G1ADD` precompile call.
+fn fpvm_g1_add(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(G1_ADD_ADDRESS, input, gas_limit, G1_ADD_INPUT_LEN, G1_ADD_GAS)
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `G1MUL` precompile call.
+fn fpvm_g1_mul(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(G1_MUL_ADDRESS, input, gas_limit, G1_MUL_INPUT_LEN, G1_MUL_GAS)
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `G1MSM` precompile call.
+fn fpvm_g1_msm(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    variable_cost_call(G1_MSM_ADDRESS, input, gas_limit, G1_MUL_INPUT_LEN, |k| {
+        msm_required_gas(k, G1_MUL_GAS)
+    })
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `G2ADD` precompile call.
+fn fpvm_g2_add(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(G2_ADD_ADDRESS, input, gas_limit, G2_ADD_INPUT_LEN, G2_ADD_GAS)
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `G2MUL` precompile call.
+fn fpvm_g2_mul(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(G2_MUL_ADDRESS, input, gas_limit, G2_MUL_INPUT_LEN, G2_MUL_GAS)
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `G2MSM` precompile call.
+fn fpvm_g2_msm(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    variable_cost_call(G2_MSM_ADDRESS, input, gas_limit, G2_MUL_INPUT_LEN, |k| {
+        msm_required_gas(k, G2_MUL_GAS)
+    })
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `PAIRING` precompile call.
+fn fpvm_pairing(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    variable_cost_call(PAIRING_ADDRESS, input, gas_limit, PAIRING_INPUT_LEN, pairing_required_gas)
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `MAP_FP_TO_G1` precompile call.
+fn fpvm_map_fp_to_g1(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(
+        MAP_FP_TO_G1_ADDRESS,
+        input,
+        gas_limit,
+        MAP_FP_TO_G1_INPUT_LEN,
+        MAP_FP_TO_G1_GAS,
+    )
+}
+
+/// Performs an FPVM-accelerated BLS12-381 `MAP_FP2_TO_G2` precompile call.
+fn fpvm_map_fp2_to_g2(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    fixed_cost_call(
+        MAP_FP2_TO_G2_ADDRESS,
+        input,
+        gas_limit,
+        MAP_FP2_TO_G2_INPUT_LEN,
+        MAP_FP2_TO_G2_GAS,
+    )
+}
+
+/// Delegates a call to a precompile with a fixed input length and gas cost to the host.
+fn fixed_cost_call(
+    address: Address,
+    input: &Bytes,
+    gas_limit: u64,
+    input_len: usize,
+    gas_cost: u64,
+) -> PrecompileResult {
+    if gas_cost > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    if input.len() != input_len {
+        return Err(PrecompileError::Other(format!(
+            "Invalid input length for precompile {address}: expected {input_len}, got {}",
+            input.len()
+        ))
+        .into());
+    }
+
+    let result_data = fpvm_precompile_call(address, input)?;
+
+    Ok(PrecompileOutput::new(gas_cost, result_data))
+}
+
+/// Delegates a call to a precompile whose input is a non-empty sequence of fixed-length items to
+/// the host. The gas cost is computed from the number of items.
+fn variable_cost_call(
+    address: Address,
+    input: &Bytes,
+    gas_limit: u64,
+    item_len: usize,
+    gas_cost: impl FnOnce(usize) -> u64,
+) -> PrecompileResult {
+    if input.is_empty() || input.len() % item_len != 0 {
+        return Err(PrecompileError::Other(format!(
+            "Invalid input length for precompile {address}: expected a multiple of {item_len}, \
+             got {}",
+            input.len()
+        ))
+        .into());
+    }
+
+    let gas_cost = gas_cost(input.len() / item_len);
+    if gas_cost > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let result_data = fpvm_precompile_call(address, input)?;
+
+    Ok(PrecompileOutput::new(gas_cost, result_data))
+}
+
+/// Computes the gas cost of a multi-scalar-multiplication over `k` pairs, given the cost of a
+/// single multiplication.
+fn msm_required_gas(k: usize, multiplication_cost: u64) -> u64 {
+    let index = k.saturating_sub(1).min(MSM_DISCOUNT_TABLE.len() - 1);
+    let discount = MSM_DISCOUNT_TABLE[index] as u64;
+
+    (k as u64 * discount * multiplication_cost) / MSM_MULTIPLIER
+}
+
+/// Computes the gas cost of a pairing check over `k` pairs.
+fn pairing_required_gas(k: usize) -> u64 {
+    PAIRING_PER_PAIR_GAS * k as u64 + PAIRING_BASE_GAS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use revm::precompile::bls12_381::{
+        g1_add, g1_msm, g1_mul, g2_add, g2_msm, g2_mul, map_fp2_to_g2, map_fp_to_g1, pairing,
+    };
+
+    /// Checks that `revm`'s implementation of the precompile is at the given address, accepts an
+    /// input of `input_len` zero bytes, and charges exactly `gas` for it. All-zero inputs encode
+    /// points at infinity and zero scalars and field elements, which are valid for every
+    /// precompile.
+    fn assert_matches_revm(
+        revm_precompile: PrecompileWithAddress,
+        address: Address,
+        input_len: usize,
+        gas: u64,
+    ) {
+        let PrecompileWithAddress(revm_address, Precompile::Standard(revm_call)) = revm_precompile
+        else {
+            panic!("Unexpected precompile type for {address}");
+        };
+        assert_eq!(revm_address, address);
+
+        let input = Bytes::from(vec![0u8; input_len]);
+        let output = revm_call(&input, gas).unwrap();
+        assert_eq!(output.gas_used, gas, "Gas mismatch for precompile {address}");
+        assert!(revm_call(&input, gas - 1).is_err());
+    }
+
+    #[test]
+    fn test_fixed_cost_precompiles_match_revm() {
+        for (revm_precompile, address, input_len, gas) in [
+            (g1_add::PRECOMPILE, G1_ADD_ADDRESS, G1_ADD_INPUT_LEN, G1_ADD_GAS),
+            (g1_mul::PRECOMPILE, G1_MUL_ADDRESS, G1_MUL_INPUT_LEN, G1_MUL_GAS),
+            (g2_add::PRECOMPILE, G2_ADD_ADDRESS, G2_ADD_INPUT_LEN, G2_ADD_GAS),
+            (g2_mul::PRECOMPILE, G2_MUL_ADDRESS, G2_MUL_INPUT_LEN, G2_MUL_GAS),
+            (
+                map_fp_to_g1::PRECOMPILE,
+                MAP_FP_TO_G1_ADDRESS,
+                MAP_FP_TO_G1_INPUT_LEN,
+                MAP_FP_TO_G1_GAS,
+            ),
+            (
+                map_fp2_to_g2::PRECOMPILE,
+                MAP_FP2_TO_G2_ADDRESS,
+                MAP_FP2_TO_G2_INPUT_LEN,
+                MAP_FP2_TO_G2_GAS,
+            ),
+        ] {
+            assert_matches_revm(revm_precompile, address, input_len, gas);
+        }
+    }
+
+    #[test]
+    fn test_variable_cost_precompiles_match_revm() {
+        // Sample the discount table at its bounds, and past its end.
+        for k in [1, 2, 64, MSM_DISCOUNT_TABLE.len(), MSM_DISCOUNT_TABLE.len() + 1] {
+            assert_matches_revm(
+                g1_msm::PRECOMPILE,
+                G1_MSM_ADDRESS,
+                k * G1_MUL_INPUT_LEN,
+                msm_required_gas(k, G1_MUL_GAS),
+            );
+            assert_matches_revm(
+                g2_msm::PRECOMPILE,
+                G2_MSM_ADDRESS,
+                k * G2_MUL_INPUT_LEN,
+                msm_required_gas(k, G2_MUL_GAS),
+            );
+        }
+
+        for k in [1, 2, 3] {
+            assert_matches_revm(
+                pairing::PRECOMPILE,
+                PAIRING_ADDRESS,
+                k * PAIRING_INPUT_LEN,
+                pairing_required_gas(k),
+            );
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Contains the accelerated version of the `ecAdd` precompile.
+
+use super::fpvm_precompile_call;
+use alloy_primitives::{Address, Bytes};
+use revm::{
+    precompile::{
+        bn128::add::ISTANBUL_ADD_GAS_COST, u64_to_address, Error as PrecompileError,
+        PrecompileWithAddress,
+    },
+    primitives::{Precompile, PrecompileOutput, PrecompileResult},
+};
+
+const ECADD_ADDRESS: Address = u64_to_address(6);
+
+pub(crate) const FPVM_ECADD: PrecompileWithAddress =
+    PrecompileWithAddress(ECADD_ADDRESS, Precompile::Standard(fpvm_ecadd));
+
+/// Performs an FPVM-accelerated `ecadd` precompile call.
+fn fpvm_ecadd(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    if ISTANBUL_ADD_GAS_COST > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let result_data = fpvm_precompile_call(ECADD_ADDRESS, input)?;
+
+    Ok(PrecompileOutput::new(ISTANBUL_ADD_GAS_COST, result_data))
+}
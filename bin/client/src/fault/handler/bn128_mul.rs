@@ -0,0 +1,27 @@
+//! Contains the accelerated version of the `ecMul` precompile.
+
+use super::fpvm_precompile_call;
+use alloy_primitives::{Address, Bytes};
+use revm::{
+    precompile::{
+        bn128::mul::ISTANBUL_MUL_GAS_COST, u64_to_address, Error as PrecompileError,
+        PrecompileWithAddress,
+    },
+    primitives::{Precompile, PrecompileOutput, PrecompileResult},
+};
+
+const ECMUL_ADDRESS: Address = u64_to_address(7);
+
+pub(crate) const FPVM_ECMUL: PrecompileWithAddress =
+    PrecompileWithAddress(ECMUL_ADDRESS, Precompile::Standard(fpvm_ecmul));
+
+/// Performs an FPVM-accelerated `ecmul` precompile call.
+fn fpvm_ecmul(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    if ISTANBUL_MUL_GAS_COST > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let result_data = fpvm_precompile_call(ECMUL_ADDRESS, input)?;
+
+    Ok(PrecompileOutput::new(ISTANBUL_MUL_GAS_COST, result_data))
+}
@@ -1,11 +1,7 @@
 //! Contains the accelerated version of the `ecPairing` precompile.
 
-use crate::fault::{HINT_WRITER, ORACLE_READER};
-use alloc::{string::ToString, vec::Vec};
-use alloy_primitives::{keccak256, Address, Bytes};
-use anyhow::ensure;
-use kona_client::HintType;
-use kona_preimage::{HintWriterClient, PreimageKey, PreimageKeyType, PreimageOracleClient};
+use super::fpvm_precompile_call;
+use alloy_primitives::{Address, Bytes};
 use revm::{
     precompile::{
         bn128::pair::{ISTANBUL_PAIR_BASE, ISTANBUL_PAIR_PER_POINT},
@@ -17,9 +13,15 @@ use revm::{
 const ECPAIRING_ADDRESS: Address = u64_to_address(8);
 const PAIR_ELEMENT_LEN: usize = 64 + 128;
 
+/// The maximum input size of the `ecPairing` precompile from Granite onwards.
+const GRANITE_MAX_INPUT_SIZE: usize = 112687;
+
 pub(crate) const FPVM_ECPAIRING: PrecompileWithAddress =
     PrecompileWithAddress(ECPAIRING_ADDRESS, Precompile::Standard(fpvm_ecpairing));
 
+pub(crate) const FPVM_ECPAIRING_GRANITE: PrecompileWithAddress =
+    PrecompileWithAddress(ECPAIRING_ADDRESS, Precompile::Standard(fpvm_ecpairing_granite));
+
 /// Performs an FPVM-accelerated `ecpairing` precompile call.
 fn fpvm_ecpairing(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let gas_used =
@@ -33,29 +35,17 @@ fn fpvm_ecpairing(input: &Bytes, gas_limit: u64) -> PrecompileResult {
         return Err(PrecompileError::Bn128PairLength.into());
     }
 
-    let result_data = kona_common::block_on(async move {
-        // Write the hint for the ecrecover precompile run.
-        let hint_data = &[ECPAIRING_ADDRESS.as_ref(), input.as_ref()];
-        HINT_WRITER.write(&HintType::L1Precompile.encode_with(hint_data)).await?;
-
-        // Construct the key hash for the ecrecover precompile run.
-        let raw_key_data = hint_data.iter().copied().flatten().copied().collect::<Vec<u8>>();
-        let key_hash = keccak256(&raw_key_data);
-
-        // Fetch the result of the ecrecover precompile run from the host.
-        let result_data =
-            ORACLE_READER.get(PreimageKey::new(*key_hash, PreimageKeyType::Precompile)).await?;
+    let result_data = fpvm_precompile_call(ECPAIRING_ADDRESS, input)?;
 
-        // Ensure we've received valid result data.
-        ensure!(!result_data.is_empty(), "Invalid result data");
-
-        // Ensure we've not received an error from the host.
-        ensure!(result_data[0] != 0, "Error executing ecrecover precompile in host");
+    Ok(PrecompileOutput::new(gas_used, result_data))
+}
 
-        // Return the result data.
-        Ok(result_data[1..].to_vec())
-    })
-    .map_err(|e| PrecompileError::Other(e.to_string()))?;
+/// Performs an FPVM-accelerated `ecpairing` precompile call, with the input size restriction that
+/// was introduced in Granite.
+fn fpvm_ecpairing_granite(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    if input.len() > GRANITE_MAX_INPUT_SIZE {
+        return Err(PrecompileError::Bn128PairLength.into());
+    }
 
-    Ok(PrecompileOutput::new(gas_used, result_data.into()))
+    fpvm_ecpairing(input, gas_limit)
 }
@@ -1,11 +1,7 @@
 //! Contains the accelerated version of the `ecrecover` precompile.
 
-use crate::fault::{HINT_WRITER, ORACLE_READER};
-use alloc::{string::ToString, vec::Vec};
-use alloy_primitives::{keccak256, Address, Bytes};
-use anyhow::ensure;
-use kona_client::HintType;
-use kona_preimage::{HintWriterClient, PreimageKey, PreimageKeyType, PreimageOracleClient};
+use super::fpvm_precompile_call;
+use alloy_primitives::{Address, Bytes};
 use revm::{
     precompile::{u64_to_address, Error as PrecompileError, PrecompileWithAddress},
     primitives::{Precompile, PrecompileOutput, PrecompileResult},
@@ -24,29 +20,7 @@ fn fpvm_ecrecover(input: &Bytes, gas_limit: u64) -> PrecompileResult {
         return Err(PrecompileError::OutOfGas.into());
     }
 
-    let result_data = kona_common::block_on(async move {
-        // Write the hint for the ecrecover precompile run.
-        let hint_data = &[ECRECOVER_ADDRESS.as_ref(), input.as_ref()];
-        HINT_WRITER.write(&HintType::L1Precompile.encode_with(hint_data)).await?;
+    let result_data = fpvm_precompile_call(ECRECOVER_ADDRESS, input)?;
 
-        // Construct the key hash for the ecrecover precompile run.
-        let raw_key_data = hint_data.iter().copied().flatten().copied().collect::<Vec<u8>>();
-        let key_hash = keccak256(&raw_key_data);
-
-        // Fetch the result of the ecrecover precompile run from the host.
-        let result_data =
-            ORACLE_READER.get(PreimageKey::new(*key_hash, PreimageKeyType::Precompile)).await?;
-
-        // Ensure we've received valid result data.
-        ensure!(!result_data.is_empty(), "Invalid result data");
-
-        // Ensure we've not received an error from the host.
-        ensure!(result_data[0] != 0, "Error executing ecrecover precompile in host");
-
-        // Return the result data.
-        Ok(result_data[1..].to_vec())
-    })
-    .map_err(|e| PrecompileError::Other(e.to_string()))?;
-
-    Ok(PrecompileOutput::new(ECRECOVER_BASE, result_data.into()))
+    Ok(PrecompileOutput::new(ECRECOVER_BASE, result_data))
 }
@@ -2,17 +2,27 @@
 //!
 //! [KonaHandleRegister]: kona_executor::KonaHandleRegister
 
-use alloc::sync::Arc;
+use crate::fault::{hint_writer, oracle_reader};
+use alloc::{string::ToString, sync::Arc};
+use alloy_primitives::{keccak256, Address, Bytes};
+use anyhow::ensure;
+use kona_client::Hint;
 use kona_mpt::{TrieDB, TrieDBFetcher, TrieDBHinter};
+use kona_preimage::{HintWriterClient, PreimageKey, PreimageKeyType, PreimageOracleClient};
 use revm::{
     handler::register::EvmHandler,
+    precompile::Error as PrecompileError,
     primitives::{spec_to_generic, SpecId},
     State,
 };
 
+mod bls12_381;
+mod bn128_add;
+mod bn128_mul;
 mod bn128_pair;
 mod ecrecover;
 mod kzg_point_eval;
+mod modexp;
 
 /// The [KonaHandleRegister] function for registering the FPVM-accelerated precompiles.
 ///
@@ -31,12 +41,55 @@ pub(crate) fn fpvm_handle_register<F, H>(
         });
 
         // Extend with FPVM-accelerated precompiles
+        let ecpairing = if spec_id.is_enabled_in(SpecId::GRANITE) {
+            bn128_pair::FPVM_ECPAIRING_GRANITE
+        } else {
+            bn128_pair::FPVM_ECPAIRING
+        };
         let override_precompiles = [
             ecrecover::FPVM_ECRECOVER,
-            bn128_pair::FPVM_ECPAIRING,
+            modexp::FPVM_MODEXP,
+            bn128_add::FPVM_ECADD,
+            bn128_mul::FPVM_ECMUL,
+            ecpairing,
             kzg_point_eval::FPVM_KZG_POINT_EVAL,
         ];
         ctx_precompiles.extend(override_precompiles);
+
+        // The BLS12-381 precompiles are only available from Prague onwards.
+        if spec_id.is_enabled_in(SpecId::PRAGUE) {
+            ctx_precompiles.extend(bls12_381::FPVM_BLS12_381_PRECOMPILES);
+        }
+
         ctx_precompiles
     });
 }
+
+/// Executes a precompile call in the host, and returns its output. The call is hinted to the host,
+/// and its result is fetched from the oracle by the hash of the precompile address and input.
+///
+/// The caller is responsible for charging gas and validating the input before delegating the call.
+fn fpvm_precompile_call(address: Address, input: &Bytes) -> Result<Bytes, PrecompileError> {
+    kona_common::block_on(async move {
+        // Write the hint for the precompile run.
+        let hint = Hint::L1Precompile { address, input: input.clone() };
+        hint_writer().write(&hint.encode()).await?;
+
+        // Construct the key hash for the precompile run.
+        let key_hash = keccak256(hint.data());
+
+        // Fetch the result of the precompile run from the host.
+        let result_data =
+            oracle_reader().get(PreimageKey::new(*key_hash, PreimageKeyType::Precompile)).await?;
+
+        // Ensure we've received valid result data.
+        ensure!(!result_data.is_empty(), "Invalid result data");
+
+        // Ensure we've not received an error from the host.
+        ensure!(result_data[0] != 0, "Error executing precompile {address} in host");
+
+        // Return the result data.
+        Ok(result_data[1..].to_vec().into())
+    })
+    .map_err(|e| PrecompileError::Other(e.to_string()))
+}
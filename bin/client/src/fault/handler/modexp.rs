@@ -0,0 +1,72 @@
+//! Contains the accelerated version of the `modexp` precompile.
+
+use super::fpvm_precompile_call;
+use alloy_primitives::{Address, Bytes, U256};
+use core::cmp::min;
+use revm::{
+    precompile::{
+        modexp::berlin_gas_calc,
+        u64_to_address,
+        utilities::{left_pad, right_pad_with_offset},
+        Error as PrecompileError, PrecompileWithAddress,
+    },
+    primitives::{Precompile, PrecompileOutput, PrecompileResult},
+};
+
+const MODEXP_ADDRESS: Address = u64_to_address(5);
+
+/// The minimum gas cost of a `modexp` call, as of EIP-2565.
+const MODEXP_MIN_GAS: u64 = 200;
+
+/// The length of the header of a `modexp` call, which holds the lengths of the base, exponent and
+/// modulus as 32-byte big-endian integers.
+const HEADER_LENGTH: usize = 96;
+
+pub(crate) const FPVM_MODEXP: PrecompileWithAddress =
+    PrecompileWithAddress(MODEXP_ADDRESS, Precompile::Standard(fpvm_modexp));
+
+/// Performs an FPVM-accelerated `modexp` precompile call. The gas cost is computed from the header
+/// of the input, as in the EIP-2565 `modexp` precompile, before the call is delegated to the host.
+fn fpvm_modexp(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    if MODEXP_MIN_GAS > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    // Extract the lengths of the base, exponent and modulus from the header.
+    let base_len = U256::from_be_bytes(right_pad_with_offset::<32>(input, 0).into_owned());
+    let exp_len = U256::from_be_bytes(right_pad_with_offset::<32>(input, 32).into_owned());
+    let mod_len = U256::from_be_bytes(right_pad_with_offset::<32>(input, 64).into_owned());
+
+    let Ok(base_len) = usize::try_from(base_len) else {
+        return Err(PrecompileError::ModexpBaseOverflow.into());
+    };
+    let Ok(mod_len) = usize::try_from(mod_len) else {
+        return Err(PrecompileError::ModexpModOverflow.into());
+    };
+
+    // Calls with an empty base and modulus have an empty output, and need not be delegated.
+    if base_len == 0 && mod_len == 0 {
+        return Ok(PrecompileOutput::new(MODEXP_MIN_GAS, Bytes::new()));
+    }
+
+    let Ok(exp_len) = usize::try_from(exp_len) else {
+        return Err(PrecompileError::ModexpModOverflow.into());
+    };
+
+    // Extract the highest 32 bytes of the exponent, which the gas cost depends on.
+    let exp_highp = {
+        let body = input.get(HEADER_LENGTH..).unwrap_or_default();
+        let right_padded_highp = right_pad_with_offset::<32>(body, base_len);
+        let out = left_pad::<32>(&right_padded_highp[..min(exp_len, 32)]);
+        U256::from_be_bytes(out.into_owned())
+    };
+
+    let gas_used = berlin_gas_calc(base_len as u64, exp_len as u64, mod_len as u64, &exp_highp);
+    if gas_used > gas_limit {
+        return Err(PrecompileError::OutOfGas.into());
+    }
+
+    let result_data = fpvm_precompile_call(MODEXP_ADDRESS, input)?;
+
+    Ok(PrecompileOutput::new(gas_used, result_data))
+}
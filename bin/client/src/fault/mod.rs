@@ -1,21 +1,169 @@
 //! Contains FPVM-specific constructs for the `kona-client` program.
+//!
+//! Within an FPVM, the client program communicates with the host over the preimage and hint pipes.
+//! When it is ran natively, it may instead connect to a host daemon, by setting the
+//! `KONA_HOST_ADDRESS` environment variable to the address that the daemon listens on.
 
+use alloc::boxed::Box;
+use alloy_primitives::B256;
+use anyhow::Result;
+use async_trait::async_trait;
 use kona_common::FileDescriptor;
-use kona_preimage::{HintWriter, OracleReader, PipeHandle};
+use kona_preimage::{Channel, HintWriter, OracleReader, PipeHandle};
+use spin::Once;
 
 mod handler;
 pub(crate) use handler::fpvm_handle_register;
 
+/// The environment variable holding the address of the host daemon to connect to, such as
+/// `unix:/tmp/kona-host.sock` or `tcp:127.0.0.1:7300`. Only read when the client program is ran
+/// natively.
+#[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+pub(crate) const HOST_ADDRESS_ENV: &str = "KONA_HOST_ADDRESS";
+
+/// The environment variable holding the L2 claim that the client program expects the host daemon to
+/// serve. A daemon serves the boot information that it was started with to every client, so it can
+/// only serve a single claim. Only read when the client program is ran natively.
+#[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+pub(crate) const EXPECTED_L2_CLAIM_ENV: &str = "KONA_L2_CLAIM";
+
 /// The global preimage oracle reader pipe.
-static ORACLE_READER_PIPE: PipeHandle =
+const ORACLE_READER_PIPE: PipeHandle =
     PipeHandle::new(FileDescriptor::PreimageRead, FileDescriptor::PreimageWrite);
 
 /// The global hint writer pipe.
-static HINT_WRITER_PIPE: PipeHandle =
+const HINT_WRITER_PIPE: PipeHandle =
     PipeHandle::new(FileDescriptor::HintRead, FileDescriptor::HintWrite);
 
-/// The global preimage oracle reader.
-pub(crate) static ORACLE_READER: OracleReader = OracleReader::new(ORACLE_READER_PIPE);
+/// The global channels to the host.
+static HOST_CHANNELS: Once<HostChannels> = Once::new();
+
+/// A [Channel] to the host, over either the FPVM's pipes or a connection to a host daemon.
+#[derive(Debug, Clone)]
+pub(crate) enum HostChannel {
+    /// A pipe to the host, through the FPVM.
+    Pipe(PipeHandle),
+    /// One of the channels of a connection to a host daemon.
+    #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+    Stream(kona_preimage::transport::StreamChannel),
+}
+
+#[async_trait]
+impl Channel for HostChannel {
+    async fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Self::Pipe(pipe) => Channel::read(pipe, buf).await,
+            #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+            Self::Stream(stream) => stream.read(buf).await,
+        }
+    }
+
+    async fn read_exact(&self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Self::Pipe(pipe) => Channel::read_exact(pipe, buf).await,
+            #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+            Self::Stream(stream) => stream.read_exact(buf).await,
+        }
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize> {
+        match self {
+            Self::Pipe(pipe) => Channel::write(pipe, buf).await,
+            #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+            Self::Stream(stream) => stream.write(buf).await,
+        }
+    }
+}
+
+/// The preimage oracle reader and hint writer that the client program communicates with the host
+/// over.
+#[derive(Debug)]
+struct HostChannels {
+    /// The preimage oracle reader.
+    oracle_reader: OracleReader<HostChannel>,
+    /// The hint writer.
+    hint_writer: HintWriter<HostChannel>,
+}
+
+impl HostChannels {
+    /// Returns the [HostChannels] over the FPVM's pipes.
+    const fn pipes() -> Self {
+        Self {
+            oracle_reader: OracleReader::new(HostChannel::Pipe(ORACLE_READER_PIPE)),
+            hint_writer: HintWriter::new(HostChannel::Pipe(HINT_WRITER_PIPE)),
+        }
+    }
+
+    /// Connects to the host daemon at the address in [HOST_ADDRESS_ENV], if it is set. Otherwise,
+    /// returns the [HostChannels] over the FPVM's pipes.
+    fn open() -> Result<Self> {
+        #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+        {
+            extern crate std;
+            use anyhow::anyhow;
+            use kona_preimage::transport::{self, TransportAddr};
+
+            if let Ok(addr) = std::env::var(HOST_ADDRESS_ENV) {
+                let addr = addr
+                    .parse::<TransportAddr>()
+                    .map_err(|e| anyhow!("Invalid {HOST_ADDRESS_ENV}: {e}"))?;
+                let transport = transport::connect(&addr)?;
+                tracing::info!(target: "client", "Connected to host daemon at {addr}");
+
+                return Ok(Self {
+                    oracle_reader: OracleReader::new(HostChannel::Stream(
+                        transport.preimage_channel(),
+                    )),
+                    hint_writer: HintWriter::new(HostChannel::Stream(transport.hint_channel())),
+                });
+            }
+        }
+
+        Ok(Self::pipes())
+    }
+}
+
+/// Opens the global channels to the host. Must be called before the client program communicates
+/// with the host, as the channels otherwise default to the FPVM's pipes.
+pub(crate) fn open_host_channels() -> Result<()> {
+    HOST_CHANNELS.try_call_once(HostChannels::open).map(|_| ())
+}
+
+/// Returns the global preimage oracle reader.
+pub(crate) fn oracle_reader() -> &'static OracleReader<HostChannel> {
+    &HOST_CHANNELS.call_once(HostChannels::pipes).oracle_reader
+}
+
+/// Returns the global hint writer.
+pub(crate) fn hint_writer() -> &'static HintWriter<HostChannel> {
+    &HOST_CHANNELS.call_once(HostChannels::pipes).hint_writer
+}
+
+/// Checks that the L2 claim served by the host matches the claim in [EXPECTED_L2_CLAIM_ENV], if it
+/// is set, so that a client program connected to a host daemon started for another claim fails
+/// instead of proving it.
+#[cfg_attr(
+    any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm"),
+    allow(unused_variables)
+)]
+pub(crate) fn check_expected_claim(l2_claim: B256) -> Result<()> {
+    #[cfg(not(any(target_arch = "mips", target_arch = "riscv64", target_os = "zkvm")))]
+    {
+        extern crate std;
+        use anyhow::anyhow;
+
+        if let Ok(expected) = std::env::var(EXPECTED_L2_CLAIM_ENV) {
+            let expected = expected
+                .parse::<B256>()
+                .map_err(|e| anyhow!("Invalid {EXPECTED_L2_CLAIM_ENV}: {e}"))?;
+            if expected != l2_claim {
+                anyhow::bail!(
+                    "The host serves the L2 claim {l2_claim}, but {EXPECTED_L2_CLAIM_ENV} is \
+                     {expected}"
+                );
+            }
+        }
+    }
 
-/// The global hint writer.
-pub(crate) static HINT_WRITER: HintWriter = HintWriter::new(HINT_WRITER_PIPE);
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Contains helpers for reading preimages that are keyed by [PreimageKeyType::GlobalGeneric] keys.
+//!
+//! Global generic keys commit to their preimage with the hash function that is tagged within them,
+//! so that new kinds of data can be made available by the host without minting a new key type.
+//! The host registers a hint handler for the new data, which stores its preimages with
+//! `Fetcher::store_global_generic`, and the client program reads them with
+//! [get_global_generic] after sending the hint.
+//!
+//! [PreimageKeyType::GlobalGeneric]: kona_preimage::PreimageKeyType::GlobalGeneric
+
+use alloc::vec::Vec;
+use anyhow::{anyhow, ensure, Result};
+use kona_preimage::{GlobalGenericHash, PreimageKey, PreimageOracleClient};
+
+/// Fetches the preimage of a global generic key from the oracle, and verifies that it hashes to
+/// the key with the key's [GlobalGenericHash].
+///
+/// ## Returns
+/// - `Ok(Vec<u8>)`: The verified preimage.
+/// - `Err(_)`: If the key is not a global generic key with a known hash function, the preimage
+///   could not be fetched, or it does not match the key.
+pub async fn get_global_generic<O>(oracle: &O, key: PreimageKey) -> Result<Vec<u8>>
+where
+    O: PreimageOracleClient + ?Sized + Sync,
+{
+    let hash = key.global_generic_hash().ok_or_else(|| {
+        anyhow!("Key {key} is not a global generic key with a known hash function")
+    })?;
+
+    let preimage = oracle.get(key).await?;
+    ensure!(
+        PreimageKey::global_generic(hash, &preimage) == key,
+        "Preimage for global generic key {key} does not match its key"
+    );
+
+    Ok(preimage)
+}
+
+/// Fetches the preimage with the given digest under the given [GlobalGenericHash] from the oracle.
+/// See [get_global_generic].
+pub async fn get_global_generic_by_digest<O>(
+    oracle: &O,
+    hash: GlobalGenericHash,
+    digest: [u8; 32],
+) -> Result<Vec<u8>>
+where
+    O: PreimageOracleClient + ?Sized + Sync,
+{
+    get_global_generic(oracle, PreimageKey::new_global_generic(hash, digest)).await
+}
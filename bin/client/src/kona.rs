@@ -8,18 +8,21 @@
 extern crate alloc;
 
 use alloc::sync::Arc;
-use alloy_consensus::Header;
+use alloy_consensus::Sealable;
 use kona_client::{
     l1::{DerivationDriver, OracleBlobProvider, OracleL1ChainProvider},
     l2::OracleL2ChainProvider,
     BootInfo, CachingOracle,
 };
+use kona_common::ExitCode;
 use kona_common_proc::client_entry;
 use kona_executor::StatelessL2BlockExecutor;
 use kona_primitives::L2AttributesWithParent;
 
 pub(crate) mod fault;
-use fault::{fpvm_handle_register, HINT_WRITER, ORACLE_READER};
+use fault::{
+    check_expected_claim, fpvm_handle_register, hint_writer, open_host_channels, oracle_reader,
+};
 
 /// The size of the LRU cache in the oracle.
 const ORACLE_LRU_SIZE: usize = 1024;
@@ -40,8 +43,18 @@ fn main() -> Result<()> {
         //                          PROLOGUE                          //
         ////////////////////////////////////////////////////////////////
 
-        let oracle = Arc::new(CachingOracle::new(ORACLE_LRU_SIZE, ORACLE_READER, HINT_WRITER));
+        // Connect to the host, and batch preimage requests if it supports them. Within an FPVM,
+        // batching is never accepted, and the oracle falls back to requesting one preimage at a
+        // time.
+        open_host_channels()?;
+        let batch_requests = hint_writer().negotiate_batch_requests().await?;
+        let oracle = Arc::new(CachingOracle::new(
+            ORACLE_LRU_SIZE,
+            oracle_reader().clone().with_batch_requests(batch_requests),
+            hint_writer().clone(),
+        ));
         let boot = Arc::new(BootInfo::load(oracle.as_ref()).await?);
+        check_expected_claim(boot.l2_claim)?;
         let l1_provider = OracleL1ChainProvider::new(boot.clone(), oracle.clone());
         let l2_provider = OracleL2ChainProvider::new(boot.clone(), oracle.clone());
         let beacon = OracleBlobProvider::new(oracle.clone());
@@ -58,23 +71,55 @@ fn main() -> Result<()> {
             l2_provider.clone(),
         )
         .await?;
-        let L2AttributesWithParent { attributes, .. } = driver.produce_disputed_payload().await?;
 
         let mut executor = StatelessL2BlockExecutor::builder(&boot.rollup_config)
-            .with_parent_header(driver.take_l2_safe_head_header())
+            .with_parent_header(driver.l2_safe_head_header().clone())
             .with_fetcher(l2_provider.clone())
             .with_hinter(l2_provider)
             .with_handle_register(fpvm_handle_register)
             .build()?;
-        let Header { number, .. } = *executor.execute_payload(attributes)?;
+
+        // Derive and execute blocks on top of the safe head until the claimed block is reached.
+        let mut number = driver.l2_safe_head().block_info.number;
+        while number < boot.l2_claim_block {
+            let L2AttributesWithParent { attributes, .. } = driver.produce_payload().await?;
+
+            let header = executor.execute_payload(attributes.clone())?.clone().seal_slow();
+            number = header.number;
+
+            driver.advance_safe_head(header, &attributes)?;
+        }
         let output_root = executor.compute_output_root()?;
 
         ////////////////////////////////////////////////////////////////
         //                          EPILOGUE                          //
         ////////////////////////////////////////////////////////////////
 
-        assert_eq!(number, boot.l2_claim_block);
-        assert_eq!(output_root, boot.l2_claim);
+        if number != boot.l2_claim_block {
+            anyhow::bail!(
+                "Derivation ended at L2 block #{number}, past the claimed block #{}",
+                boot.l2_claim_block
+            );
+        }
+
+        if output_root != boot.l2_claim {
+            tracing::error!(
+                target: "client",
+                "Failed to validate L2 block #{number} with claim {claim}: derived output root {output_root}",
+                number = number,
+                claim = boot.l2_claim,
+                output_root = output_root
+            );
+
+            kona_common::io::print_err(&alloc::format!(
+                "Failed to validate L2 block #{} with claim {}: derived output root {}\n",
+                number,
+                boot.l2_claim,
+                output_root
+            ));
+
+            return Ok(ExitCode::Invalid);
+        }
 
         tracing::info!(
             target: "client",
@@ -89,6 +134,6 @@ fn main() -> Result<()> {
             output_root
         ));
 
-        Ok::<_, anyhow::Error>(())
+        Ok::<_, anyhow::Error>(ExitCode::Valid)
     })
 }
@@ -1,10 +1,11 @@
 //! Contains the concrete implementation of the [BlobProvider] trait for the client program.
 
-use crate::HintType;
+use crate::Hint;
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use alloy_consensus::Blob;
 use alloy_eips::eip4844::FIELD_ELEMENTS_PER_BLOB;
 use alloy_primitives::keccak256;
+use anyhow::anyhow;
 use async_trait::async_trait;
 use kona_derive::{errors::BlobProviderError, traits::BlobProvider};
 use kona_preimage::{CommsClient, PreimageKey, PreimageKeyType};
@@ -21,7 +22,9 @@ impl<T: CommsClient> OracleBlobProvider<T> {
     pub fn new(oracle: Arc<T>) -> Self {
         Self { oracle }
     }
+}
 
+impl<T: CommsClient + Sync + Send> OracleBlobProvider<T> {
     /// Retrieves a blob from the oracle.
     ///
     /// ## Takes
@@ -36,13 +39,13 @@ impl<T: CommsClient> OracleBlobProvider<T> {
         block_ref: &BlockInfo,
         blob_hash: &IndexedBlobHash,
     ) -> Result<Blob, BlobProviderError> {
-        let mut blob_req_meta = [0u8; 48];
-        blob_req_meta[0..32].copy_from_slice(blob_hash.hash.as_ref());
-        blob_req_meta[32..40].copy_from_slice((blob_hash.index as u64).to_be_bytes().as_ref());
-        blob_req_meta[40..48].copy_from_slice(block_ref.timestamp.to_be_bytes().as_ref());
-
         // Send a hint for the blob commitment and field elements.
-        self.oracle.write(&HintType::L1Blob.encode_with(&[blob_req_meta.as_ref()])).await?;
+        let hint = Hint::L1Blob {
+            hash: blob_hash.hash,
+            index: blob_hash.index as u64,
+            timestamp: block_ref.timestamp,
+        };
+        self.oracle.write(&hint.encode()).await?;
 
         // Fetch the blob commitment.
         let mut commitment = [0u8; 48];
@@ -50,21 +53,27 @@ impl<T: CommsClient> OracleBlobProvider<T> {
             .get_exact(PreimageKey::new(*blob_hash.hash, PreimageKeyType::Sha256), &mut commitment)
             .await?;
 
-        // Reconstruct the blob from the 4096 field elements.
-        let mut blob = Blob::default();
+        // Fetch the 4096 field elements of the blob, in batches if the host supports them.
         let mut field_element_key = [0u8; 80];
         field_element_key[..48].copy_from_slice(commitment.as_ref());
-        for i in 0..FIELD_ELEMENTS_PER_BLOB {
-            field_element_key[72..].copy_from_slice(i.to_be_bytes().as_ref());
+        let keys = (0..FIELD_ELEMENTS_PER_BLOB)
+            .map(|i| {
+                field_element_key[72..].copy_from_slice(i.to_be_bytes().as_ref());
+                PreimageKey::new(*keccak256(field_element_key), PreimageKeyType::Blob)
+            })
+            .collect::<Vec<_>>();
+        let field_elements = self.oracle.get_batch(&keys).await?;
 
-            let mut field_element = [0u8; 32];
-            self.oracle
-                .get_exact(
-                    PreimageKey::new(*keccak256(field_element_key), PreimageKeyType::Blob),
-                    &mut field_element,
-                )
-                .await?;
-            blob[(i as usize) << 5..(i as usize + 1) << 5].copy_from_slice(field_element.as_ref());
+        // Reconstruct the blob from the field elements.
+        let mut blob = Blob::default();
+        for (i, field_element) in field_elements.iter().enumerate() {
+            if field_element.len() != 32 {
+                return Err(BlobProviderError::Custom(anyhow!(
+                    "Invalid field element length {} for blob {blob_hash:?}",
+                    field_element.len()
+                )));
+            }
+            blob[i << 5..(i + 1) << 5].copy_from_slice(field_element);
         }
 
         tracing::info!(target: "client_oracle", "Retrieved blob {blob_hash:?} from the oracle.");
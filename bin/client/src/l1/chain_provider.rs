@@ -1,15 +1,15 @@
 //! Contains the concrete implementation of the [ChainProvider] trait for the client program.
 
-use crate::{BootInfo, HintType};
+use crate::{BootInfo, Hint};
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
-use alloy_consensus::{Header, Receipt, ReceiptEnvelope, TxEnvelope};
+use alloy_consensus::{Header, Receipt, ReceiptEnvelope, Sealed, TxEnvelope};
 use alloy_eips::eip2718::Decodable2718;
-use alloy_primitives::{Bytes, B256};
+use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_rlp::Decodable;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use kona_derive::traits::ChainProvider;
-use kona_mpt::{OrderedListWalker, TrieDBFetcher};
+use kona_mpt::{OrderedListWalker, TrieDB, TrieDBFetcher, TrieDBHinter};
 use kona_preimage::{CommsClient, PreimageKey, PreimageKeyType};
 use kona_primitives::BlockInfo;
 
@@ -29,11 +29,23 @@ impl<T: CommsClient> OracleL1ChainProvider<T> {
     }
 }
 
+impl<T: CommsClient + Sync + Send> OracleL1ChainProvider<T> {
+    /// Returns a [TrieDB] over the state of the L1 block with the given hash. Accounts and storage
+    /// slots read through the [TrieDB] are hinted to the host as L1 proofs, and verified against
+    /// the state root of the block.
+    pub async fn state_at(&mut self, hash: B256) -> Result<TrieDB<Self, Self>> {
+        let header = ChainProvider::header_by_hash(self, hash).await?;
+        let fetcher = Self::new(self.boot_info.clone(), self.oracle.clone());
+        let hinter = Self::new(self.boot_info.clone(), self.oracle.clone());
+        Ok(TrieDB::new(header.state_root, Sealed::new_unchecked(header, hash), fetcher, hinter))
+    }
+}
+
 #[async_trait]
 impl<T: CommsClient + Sync + Send> ChainProvider for OracleL1ChainProvider<T> {
     async fn header_by_hash(&mut self, hash: B256) -> Result<Header> {
         // Send a hint for the block header.
-        self.oracle.write(&HintType::L1BlockHeader.encode_with(&[hash.as_ref()])).await?;
+        self.oracle.write(&Hint::L1BlockHeader { hash }.encode()).await?;
 
         // Fetch the header RLP from the oracle.
         let header_rlp =
@@ -72,7 +84,7 @@ impl<T: CommsClient + Sync + Send> ChainProvider for OracleL1ChainProvider<T> {
 
         // Send a hint for the block's receipts, and walk through the receipts trie in the header to
         // verify them.
-        self.oracle.write(&HintType::L1Receipts.encode_with(&[hash.as_ref()])).await?;
+        self.oracle.write(&Hint::L1Receipts { hash }.encode()).await?;
         let trie_walker = OrderedListWalker::try_new_hydrated(header.receipts_root, self)?;
 
         // Decode the receipts within the transactions trie.
@@ -103,7 +115,7 @@ impl<T: CommsClient + Sync + Send> ChainProvider for OracleL1ChainProvider<T> {
 
         // Send a hint for the block's transactions, and walk through the transactions trie in the
         // header to verify them.
-        self.oracle.write(&HintType::L1Transactions.encode_with(&[hash.as_ref()])).await?;
+        self.oracle.write(&Hint::L1Transactions { hash }.encode()).await?;
         let trie_walker = OrderedListWalker::try_new_hydrated(header.transactions_root, self)?;
 
         // Decode the transactions within the transactions trie.
@@ -131,11 +143,56 @@ impl<T: CommsClient> TrieDBFetcher for OracleL1ChainProvider<T> {
         })
     }
 
-    fn bytecode_by_hash(&self, _: B256) -> Result<Bytes> {
-        unimplemented!("TrieDBFetcher::bytecode_by_hash unimplemented for OracleL1ChainProvider")
+    fn bytecode_by_hash(&self, hash: B256) -> Result<Bytes> {
+        // Fetch the bytecode preimage from the oracle.
+        kona_common::block_on(async move {
+            self.oracle.write(&Hint::L1Code { hash }.encode()).await?;
+
+            self.oracle
+                .get(PreimageKey::new(*hash, PreimageKeyType::Keccak256))
+                .await
+                .map(Into::into)
+        })
     }
 
-    fn header_by_hash(&self, _: B256) -> Result<Header> {
-        unimplemented!("TrieDBFetcher::header_by_hash unimplemented for OracleL1ChainProvider")
+    fn header_by_hash(&self, hash: B256) -> Result<Header> {
+        // Fetch the header from the oracle.
+        kona_common::block_on(async move {
+            self.oracle.write(&Hint::L1BlockHeader { hash }.encode()).await?;
+
+            let header_bytes =
+                self.oracle.get(PreimageKey::new(*hash, PreimageKeyType::Keccak256)).await?;
+            Header::decode(&mut header_bytes.as_slice())
+                .map_err(|e| anyhow!("Failed to RLP decode Header: {e}"))
+        })
+    }
+}
+
+impl<T: CommsClient> TrieDBHinter for OracleL1ChainProvider<T> {
+    fn hint_trie_node(&self, hash: B256) -> Result<()> {
+        kona_common::block_on(async move {
+            self.oracle.write(&Hint::L1StateNode { hash }.encode()).await
+        })
+    }
+
+    fn hint_account_proof(&self, address: Address, block_number: u64) -> Result<()> {
+        kona_common::block_on(async move {
+            self.oracle.write(&Hint::L1AccountProof { block_number, address }.encode()).await
+        })
+    }
+
+    fn hint_storage_proof(&self, address: Address, slot: U256, block_number: u64) -> Result<()> {
+        kona_common::block_on(async move {
+            self.oracle
+                .write(
+                    &Hint::L1AccountStorageProof {
+                        block_number,
+                        address,
+                        slot: slot.to_be_bytes().into(),
+                    }
+                    .encode(),
+                )
+                .await
+        })
     }
 }
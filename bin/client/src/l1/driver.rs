@@ -4,9 +4,10 @@
 //! [L2PayloadAttributes]: kona_primitives::L2PayloadAttributes
 
 use super::OracleL1ChainProvider;
-use crate::{l2::OracleL2ChainProvider, BootInfo, HintType};
+use crate::{l2::OracleL2ChainProvider, BootInfo, Hint};
 use alloc::sync::Arc;
 use alloy_consensus::{Header, Sealed};
+use alloy_eips::eip2718::Decodable2718;
 use anyhow::{anyhow, Result};
 use core::fmt::Debug;
 use kona_derive::{
@@ -21,7 +22,10 @@ use kona_derive::{
 };
 use kona_mpt::TrieDBFetcher;
 use kona_preimage::{CommsClient, PreimageKey, PreimageKeyType};
-use kona_primitives::{BlockInfo, L2AttributesWithParent, L2BlockInfo};
+use kona_primitives::{
+    BlockInfo, L1BlockInfoTx, L2AttributesWithParent, L2BlockInfo, L2PayloadAttributes, OutputRoot,
+};
+use op_alloy_consensus::OpTxEnvelope;
 use tracing::{info, warn};
 
 /// An oracle-backed derivation pipeline.
@@ -66,6 +70,8 @@ where
     l2_safe_head: L2BlockInfo,
     /// The header of the L2 safe head.
     l2_safe_head_header: Sealed<Header>,
+    /// The L2 chain provider, which the blocks executed on top of the safe head are inserted into.
+    l2_chain_provider: OracleL2ChainProvider<O>,
     /// The inner pipeline.
     pipeline: OraclePipeline<O, B>,
 }
@@ -140,21 +146,22 @@ where
         let pipeline = PipelineBuilder::new()
             .rollup_config(cfg)
             .dap_source(dap)
-            .l2_chain_provider(l2_chain_provider)
+            .l2_chain_provider(l2_chain_provider.clone())
             .chain_provider(chain_provider)
             .builder(attributes)
             .origin(l1_origin)
             .build();
 
-        Ok(Self { l2_safe_head, l2_safe_head_header, pipeline })
+        Ok(Self { l2_safe_head, l2_safe_head_header, l2_chain_provider, pipeline })
     }
 
-    /// Produces the disputed [L2AttributesWithParent] payload, directly after the starting L2
-    /// output root passed through the [BootInfo].
-    pub async fn produce_disputed_payload(&mut self) -> Result<L2AttributesWithParent> {
+    /// Produces the next [L2AttributesWithParent] payload, directly after the current L2 safe
+    /// head. On the first call, this is the payload directly after the starting L2 output root
+    /// passed through the [BootInfo].
+    pub async fn produce_payload(&mut self) -> Result<L2AttributesWithParent> {
         // As we start the safe head at the disputed block's parent, we step the pipeline until the
         // first attributes are produced. All batches at and before the safe head will be
-        // dropped, so the first payload will always be the disputed one.
+        // dropped, so the first payload will always be the one directly after the safe head.
         let mut attributes = None;
         while attributes.is_none() {
             match self.pipeline.step(self.l2_safe_head).await {
@@ -179,7 +186,63 @@ where
             attributes = self.pipeline.next();
         }
 
-        Ok(attributes.expect("Failed to derive payload attributes"))
+        attributes.ok_or_else(|| anyhow!("Failed to derive payload attributes"))
+    }
+
+    /// Advances the L2 safe head to the given block, which must have been produced by executing
+    /// the `attributes` on top of the current L2 safe head. Subsequent calls to
+    /// [Self::produce_payload] will derive the payload directly after this block.
+    ///
+    /// ## Takes
+    /// - `header`: The sealed [Header] of the newly executed block.
+    /// - `attributes`: The [L2PayloadAttributes] that the block was built from.
+    ///
+    /// ## Returns
+    /// - `Ok(())` if the safe head was advanced.
+    /// - `Err(_)` if the block does not extend the current safe head, or its L1 info deposit
+    ///   transaction or any of its other transactions could not be decoded.
+    pub fn advance_safe_head(
+        &mut self,
+        header: Sealed<Header>,
+        attributes: &L2PayloadAttributes,
+    ) -> Result<()> {
+        if header.parent_hash != self.l2_safe_head.block_info.hash {
+            anyhow::bail!(
+                "Block #{} does not extend the L2 safe head #{}",
+                header.number,
+                self.l2_safe_head.block_info.number
+            );
+        }
+
+        // Decode the L1 origin and sequence number from the L1 info deposit transaction, which is
+        // always the first transaction in the block.
+        let raw_l1_info = attributes
+            .transactions
+            .first()
+            .ok_or_else(|| anyhow!("Payload is missing the L1 info deposit transaction"))?;
+        let OpTxEnvelope::Deposit(l1_info_tx) =
+            OpTxEnvelope::decode_2718(&mut raw_l1_info.as_ref())
+                .map_err(|e| anyhow!("Failed to decode L1 info deposit transaction: {e}"))?
+        else {
+            anyhow::bail!("First payload transaction is not a deposit");
+        };
+        let l1_info = L1BlockInfoTx::decode_calldata(l1_info_tx.input.as_ref())?;
+
+        self.l2_safe_head = L2BlockInfo::new(
+            BlockInfo::new(header.seal(), header.number, header.parent_hash, header.timestamp),
+            l1_info.id(),
+            l1_info.sequence_number(),
+        );
+        self.l2_chain_provider.insert_block(header.clone(), &attributes.transactions)?;
+        self.l2_safe_head_header = header;
+
+        info!(
+            target: "client_derivation_driver",
+            "Advanced L2 safe head to block #{}",
+            self.l2_safe_head.block_info.number
+        );
+
+        Ok(())
     }
 
     /// Finds the startup information for the derivation pipeline.
@@ -200,18 +263,17 @@ where
     ) -> Result<(BlockInfo, L2BlockInfo, Sealed<Header>)> {
         // Find the initial safe head, based off of the starting L2 block number in the boot info.
         caching_oracle
-            .write(&HintType::StartingL2Output.encode_with(&[boot_info.l2_output_root.as_ref()]))
+            .write(&Hint::StartingL2Output { output_root: boot_info.l2_output_root }.encode())
             .await?;
-        let mut output_preimage = [0u8; 128];
-        caching_oracle
-            .get_exact(
-                PreimageKey::new(*boot_info.l2_output_root, PreimageKeyType::Keccak256),
-                &mut output_preimage,
-            )
+        let output_preimage = caching_oracle
+            .get(PreimageKey::new(*boot_info.l2_output_root, PreimageKeyType::Keccak256))
             .await?;
+        let output = OutputRoot::decode(&output_preimage)?;
+        if output.hash() != boot_info.l2_output_root {
+            anyhow::bail!("Invalid L2 output root preimage");
+        }
 
-        let safe_hash =
-            output_preimage[96..128].try_into().map_err(|_| anyhow!("Invalid L2 output root"))?;
+        let safe_hash = output.block_hash();
         let safe_header = l2_chain_provider.header_by_hash(safe_hash)?;
         let safe_head_info = l2_chain_provider.l2_block_info_by_number(safe_header.number).await?;
 
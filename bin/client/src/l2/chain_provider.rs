@@ -1,8 +1,8 @@
 //! Contains the concrete implementation of the [L2ChainProvider] trait for the client program.
 
-use crate::{BootInfo, HintType};
-use alloc::{boxed::Box, sync::Arc, vec::Vec};
-use alloy_consensus::Header;
+use crate::{BootInfo, Hint};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Sealed};
 use alloy_eips::eip2718::Decodable2718;
 use alloy_primitives::{Address, Bytes, B256};
 use alloy_rlp::Decodable;
@@ -12,9 +12,11 @@ use kona_derive::traits::L2ChainProvider;
 use kona_mpt::{OrderedListWalker, TrieDBFetcher, TrieDBHinter};
 use kona_preimage::{CommsClient, PreimageKey, PreimageKeyType};
 use kona_primitives::{
-    L2BlockInfo, L2ExecutionPayloadEnvelope, OpBlock, RollupConfig, SystemConfig,
+    L2BlockInfo, L2ExecutionPayloadEnvelope, OpBlock, OutputRoot, RawTransaction, RollupConfig,
+    SystemConfig,
 };
 use op_alloy_consensus::OpTxEnvelope;
+use spin::Mutex;
 
 /// The oracle-backed L2 chain provider for the client program.
 #[derive(Debug, Clone)]
@@ -23,12 +25,58 @@ pub struct OracleL2ChainProvider<T: CommsClient> {
     boot_info: Arc<BootInfo>,
     /// The preimage oracle client.
     oracle: Arc<T>,
+    /// The hash of the latest L2 block known to the provider. If [None], the block committed to
+    /// by the agreed L2 output root is used.
+    cursor: Arc<Mutex<Option<B256>>>,
+    /// The L2 blocks executed by the client program, keyed by hash. These blocks may not exist in
+    /// the host's view of the L2 chain, so they are served from memory rather than the oracle.
+    executed_blocks: Arc<Mutex<BTreeMap<B256, OpBlock>>>,
 }
 
 impl<T: CommsClient> OracleL2ChainProvider<T> {
     /// Creates a new [OracleL2ChainProvider] with the given boot information and oracle client.
     pub fn new(boot_info: Arc<BootInfo>, oracle: Arc<T>) -> Self {
-        Self { boot_info, oracle }
+        Self {
+            boot_info,
+            oracle,
+            cursor: Arc::new(Mutex::new(None)),
+            executed_blocks: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Inserts a block executed by the client program, and makes it the latest L2 block known to
+    /// the provider. Headers are looked up by walking back from this block, so it must be inserted
+    /// as new blocks are executed on top of the safe head. The executed blocks are shared between
+    /// all clones of the provider.
+    pub fn insert_block(
+        &self,
+        header: Sealed<Header>,
+        transactions: &[RawTransaction],
+    ) -> Result<()> {
+        let body = transactions
+            .iter()
+            .map(|tx| {
+                OpTxEnvelope::decode_2718(&mut tx.as_ref())
+                    .map_err(|e| anyhow!("Failed to decode TxEnvelope RLP: {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let hash = header.seal();
+        let header = header.unseal();
+        let block = OpBlock {
+            withdrawals: self
+                .boot_info
+                .rollup_config
+                .is_canyon_active(header.timestamp)
+                .then(Vec::new),
+            header,
+            body,
+            ..Default::default()
+        };
+
+        self.executed_blocks.lock().insert(hash, block);
+        *self.cursor.lock() = Some(hash);
+        Ok(())
     }
 }
 
@@ -36,21 +84,35 @@ impl<T: CommsClient> OracleL2ChainProvider<T> {
     /// Returns a [Header] corresponding to the given L2 block number, by walking back from the
     /// L2 safe head.
     async fn header_by_number(&mut self, block_number: u64) -> Result<Header> {
-        // Fetch the starting L2 output preimage.
-        self.oracle
-            .write(
-                &HintType::StartingL2Output.encode_with(&[self.boot_info.l2_output_root.as_ref()]),
-            )
-            .await?;
-        let output_preimage = self
-            .oracle
-            .get(PreimageKey::new(*self.boot_info.l2_output_root, PreimageKeyType::Keccak256))
-            .await?;
+        let cursor = *self.cursor.lock();
+        let block_hash = match cursor {
+            Some(hash) => hash,
+            None => {
+                // Fetch the starting L2 output preimage.
+                self.oracle
+                    .write(
+                        &Hint::StartingL2Output { output_root: self.boot_info.l2_output_root }
+                            .encode(),
+                    )
+                    .await?;
+                let output_preimage = self
+                    .oracle
+                    .get(PreimageKey::new(
+                        *self.boot_info.l2_output_root,
+                        PreimageKeyType::Keccak256,
+                    ))
+                    .await?;
+
+                // Decode the output root, and extract the starting block hash.
+                let output = OutputRoot::decode(&output_preimage)?;
+                if output.hash() != self.boot_info.l2_output_root {
+                    anyhow::bail!("Invalid L2 output root preimage");
+                }
+                output.block_hash()
+            }
+        };
 
         // Fetch the starting block header.
-        let block_hash = output_preimage[96..128]
-            .try_into()
-            .map_err(|e| anyhow!("Failed to extract block hash from output preimage: {e}"))?;
         let mut header = self.header_by_hash(block_hash)?;
 
         // Check if the block number is in range. If not, we can fail early.
@@ -83,8 +145,13 @@ impl<T: CommsClient + Send + Sync> L2ChainProvider for OracleL2ChainProvider<T>
             self.header_by_number(number).await?;
         let header_hash = header.hash_slow();
 
+        // Serve the blocks executed by the client program from memory.
+        if let Some(block) = self.executed_blocks.lock().get(&header_hash) {
+            return Ok(block.clone().into());
+        }
+
         // Fetch the transactions in the block.
-        self.oracle.write(&HintType::L2Transactions.encode_with(&[header_hash.as_ref()])).await?;
+        self.oracle.write(&Hint::L2Transactions { hash: header_hash }.encode()).await?;
         let trie_walker = OrderedListWalker::try_new_hydrated(transactions_root, self)?;
 
         // Decode the transactions within the transactions trie.
@@ -133,7 +200,7 @@ impl<T: CommsClient> TrieDBFetcher for OracleL2ChainProvider<T> {
     fn bytecode_by_hash(&self, hash: B256) -> Result<Bytes> {
         // Fetch the bytecode preimage from the caching oracle.
         kona_common::block_on(async move {
-            self.oracle.write(&HintType::L2Code.encode_with(&[hash.as_ref()])).await?;
+            self.oracle.write(&Hint::L2Code { hash }.encode()).await?;
 
             self.oracle
                 .get(PreimageKey::new(*hash, PreimageKeyType::Keccak256))
@@ -143,9 +210,14 @@ impl<T: CommsClient> TrieDBFetcher for OracleL2ChainProvider<T> {
     }
 
     fn header_by_hash(&self, hash: B256) -> Result<Header> {
+        // Serve the headers of the blocks executed by the client program from memory.
+        if let Some(block) = self.executed_blocks.lock().get(&hash) {
+            return Ok(block.header.clone());
+        }
+
         // Fetch the header from the caching oracle.
         kona_common::block_on(async move {
-            self.oracle.write(&HintType::L2BlockHeader.encode_with(&[hash.as_ref()])).await?;
+            self.oracle.write(&Hint::L2BlockHeader { hash }.encode()).await?;
 
             let header_bytes =
                 self.oracle.get(PreimageKey::new(*hash, PreimageKeyType::Keccak256)).await?;
@@ -158,18 +230,13 @@ impl<T: CommsClient> TrieDBFetcher for OracleL2ChainProvider<T> {
 impl<T: CommsClient> TrieDBHinter for OracleL2ChainProvider<T> {
     fn hint_trie_node(&self, hash: B256) -> Result<()> {
         kona_common::block_on(async move {
-            self.oracle.write(&HintType::L2StateNode.encode_with(&[hash.as_slice()])).await
+            self.oracle.write(&Hint::L2StateNode { hash }.encode()).await
         })
     }
 
     fn hint_account_proof(&self, address: Address, block_number: u64) -> Result<()> {
         kona_common::block_on(async move {
-            self.oracle
-                .write(
-                    &HintType::L2AccountProof
-                        .encode_with(&[block_number.to_be_bytes().as_ref(), address.as_slice()]),
-                )
-                .await
+            self.oracle.write(&Hint::L2AccountProof { block_number, address }.encode()).await
         })
     }
 
@@ -181,11 +248,14 @@ impl<T: CommsClient> TrieDBHinter for OracleL2ChainProvider<T> {
     ) -> Result<()> {
         kona_common::block_on(async move {
             self.oracle
-                .write(&HintType::L2AccountStorageProof.encode_with(&[
-                    block_number.to_be_bytes().as_ref(),
-                    address.as_slice(),
-                    slot.to_be_bytes::<32>().as_ref(),
-                ]))
+                .write(
+                    &Hint::L2AccountStorageProof {
+                        block_number,
+                        address,
+                        slot: slot.to_be_bytes().into(),
+                    }
+                    .encode(),
+                )
                 .await
         })
     }
@@ -10,11 +10,12 @@ pub mod l1;
 
 pub mod l2;
 
-mod hint;
-pub use hint::HintType;
+pub use kona_primitives::{Hint, HintType};
 
 pub mod boot;
 pub use boot::BootInfo;
 
 mod caching_oracle;
 pub use caching_oracle::CachingOracle;
+
+pub mod global_generic;
@@ -0,0 +1,38 @@
+//! Migrates preimages from the one-file-per-key directory layout of the [DiskKeyValueStore] into
+//! the embedded database of the [EmbeddedKeyValueStore].
+
+use anyhow::Result;
+use clap::{ArgAction, Parser};
+use kona_host::{
+    init_tracing_subscriber,
+    kv::{DiskKeyValueStore, EmbeddedKeyValueStore},
+};
+use std::path::PathBuf;
+use tracing::info;
+
+/// The migration CLI arguments.
+#[derive(Parser, Clone, Debug)]
+struct MigrateCli {
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    v: u8,
+    /// The data directory written by the `directory` key-value backend.
+    #[clap(long)]
+    from: PathBuf,
+    /// The data directory to write the `embedded` key-value backend's database into. May be the
+    /// same as `--from`.
+    #[clap(long)]
+    data_dir: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cfg = MigrateCli::parse();
+    init_tracing_subscriber(cfg.v)?;
+
+    let disk_kv_store = DiskKeyValueStore::new(cfg.from);
+    let mut embedded_kv_store = EmbeddedKeyValueStore::new(cfg.data_dir)?;
+    let migrated = embedded_kv_store.import_from_disk(&disk_kv_store)?;
+
+    info!("Migrated {migrated} preimages.");
+    Ok(())
+}
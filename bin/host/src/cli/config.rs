@@ -0,0 +1,200 @@
+//! Contains the logic for merging a `--config` file into the [HostCli] arguments, and for
+//! validating the resulting configuration before the host starts.
+
+use super::HostCli;
+use anyhow::{anyhow, Result};
+use clap::{parser::ValueSource, ArgAction, CommandFactory, Parser, ValueEnum};
+use kona_primitives::RollupConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, ffi::OsString, path::Path};
+
+/// The chains that the host has presets for. Selecting a preset sets the L2 chain ID, and the
+/// rollup config is resolved from the superchain registry.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainPreset {
+    /// OP Mainnet.
+    OpMainnet,
+    /// OP Sepolia.
+    OpSepolia,
+    /// Base Mainnet.
+    BaseMainnet,
+    /// Base Sepolia.
+    BaseSepolia,
+}
+
+impl ChainPreset {
+    /// Returns the L2 chain ID of the preset.
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Self::OpMainnet => 10,
+            Self::OpSepolia => 11155420,
+            Self::BaseMainnet => 8453,
+            Self::BaseSepolia => 84532,
+        }
+    }
+}
+
+/// A single value within a `--config` file.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ConfigValue {
+    /// A flag, such as `server = true`.
+    Bool(bool),
+    /// A numeric option, such as `l2-block-number = 100`. For counted flags such as `v`, the
+    /// number of occurrences.
+    Integer(u64),
+    /// Any other option, such as `l1-node-address = "http://localhost:8545"`.
+    String(String),
+}
+
+impl HostCli {
+    /// Parses the [HostCli] from the process arguments, merged with the `--config` file if one is
+    /// given. See [Self::parse_with_config_from].
+    pub fn parse_with_config() -> Result<Self> {
+        Self::parse_with_config_from(std::env::args_os())
+    }
+
+    /// Parses the [HostCli] from the given arguments, merged with the `--config` file if one is
+    /// given.
+    ///
+    /// The config file is a TOML or JSON table, chosen by the file extension, whose keys are the
+    /// long names of the host's flags, such as `l1-node-address`. Flags passed on the command line
+    /// or through their environment variables take precedence over the config file, and config
+    /// file entries that conflict with them are ignored.
+    pub fn parse_with_config_from<I, T>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+
+        // Parse the arguments leniently first, to find the config file and the flags that were set
+        // explicitly. Missing required flags may still be supplied by the config file.
+        let command = Self::command();
+        let matches = command.clone().ignore_errors(true).get_matches_from(args.clone());
+
+        if let Some(path) = matches.get_one::<std::path::PathBuf>("config") {
+            let explicit = command
+                .get_arguments()
+                .filter(|arg| {
+                    matches!(
+                        matches.value_source(arg.get_id().as_str()),
+                        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            for (key, value) in read_config_file(path)? {
+                let key = key.replace('_', "-");
+                let arg = command
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(key.as_str()))
+                    .ok_or_else(|| anyhow!("Unknown option `{key}` in config file"))?;
+                if arg.get_id() == "config" {
+                    anyhow::bail!("Config files may not include other config files");
+                }
+
+                // Explicit flags take precedence over the config file, including over entries
+                // that conflict with them.
+                let overridden = explicit.iter().any(|explicit| {
+                    explicit.get_id() == arg.get_id() ||
+                        command.get_arg_conflicts_with(arg).contains(explicit) ||
+                        command.get_arg_conflicts_with(explicit).contains(&arg)
+                });
+                if overridden {
+                    continue;
+                }
+
+                match (arg.get_action(), value) {
+                    (ArgAction::SetTrue, ConfigValue::Bool(set)) => {
+                        if set {
+                            args.push(format!("--{key}").into());
+                        }
+                    }
+                    (ArgAction::Count, ConfigValue::Integer(count)) => {
+                        args.extend((0..count).map(|_| format!("--{key}").into()));
+                    }
+                    (ArgAction::Set, ConfigValue::Integer(value)) => {
+                        args.push(format!("--{key}={value}").into());
+                    }
+                    (ArgAction::Set, ConfigValue::String(value)) => {
+                        args.push(format!("--{key}={value}").into());
+                    }
+                    (_, value) => {
+                        anyhow::bail!("Invalid value for `{key}` in config file: {value:?}")
+                    }
+                }
+            }
+        }
+
+        let mut cfg = Self::parse_from(args);
+        if let Some(chain) = cfg.chain {
+            cfg.l2_chain_id = chain.chain_id();
+        }
+        Ok(cfg)
+    }
+
+    /// Validates the configuration, so that missing or inconsistent options are reported before
+    /// the host starts rather than once they are first used.
+    pub fn validate(&self) -> Result<()> {
+        if self.witness.is_none() {
+            let endpoints = [
+                ("--l1-node-address", self.l1_node_address.is_some()),
+                ("--l2-node-address", self.l2_node_address.is_some()),
+                ("--l1-beacon-address", self.l1_beacon_address.is_some()),
+            ];
+            let missing = endpoints
+                .iter()
+                .filter_map(|(flag, set)| (!set).then_some(*flag))
+                .collect::<Vec<_>>();
+
+            if missing.len() == endpoints.len() && self.data_dir.is_none() {
+                anyhow::bail!(
+                    "No preimage source configured. Set the L1, L2 and beacon endpoints to run \
+                     online, or --witness or --data-dir to run offline"
+                );
+            }
+            if !missing.is_empty() && missing.len() < endpoints.len() {
+                anyhow::bail!(
+                    "Missing {}. The L1, L2 and beacon endpoints must all be set to run online",
+                    missing.join(", ")
+                );
+            }
+
+            match self.rollup_config_path {
+                Some(ref path) if !path.is_file() => {
+                    anyhow::bail!("Rollup config file {} does not exist", path.display())
+                }
+                None if RollupConfig::from_l2_chain_id(self.l2_chain_id).is_none() => {
+                    anyhow::bail!(
+                        "No rollup config is known for L2 chain ID {}. Pass --rollup-config-path \
+                         for chains outside of the superchain registry",
+                        self.l2_chain_id
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        if !self.server && self.listen.is_none() && self.exec.is_none() {
+            anyhow::bail!("No client program configured. Pass --exec, or run with --server");
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the entries of a `--config` file. Files with a `.json` extension are parsed as JSON, and
+/// all other files as TOML.
+fn read_config_file(path: &Path) -> Result<BTreeMap<String, ConfigValue>> {
+    let ser_config = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Error reading config file {}: {e}", path.display()))?;
+
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&ser_config)
+            .map_err(|e| anyhow!("Error deserializing config file {}: {e}", path.display()))
+    } else {
+        toml::from_str(&ser_config)
+            .map_err(|e| anyhow!("Error deserializing config file {}: {e}", path.display()))
+    }
+}
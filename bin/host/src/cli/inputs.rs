@@ -0,0 +1,72 @@
+//! Contains the logic for deriving the starting inputs of the client program from the L1 and L2
+//! nodes, for use with `--derive-inputs`.
+
+use super::HostCli;
+use crate::{rpc_cache::RpcProvider, util};
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::B256;
+use alloy_provider::Provider;
+use anyhow::{anyhow, Result};
+use tracing::info;
+
+impl HostCli {
+    /// Derives the L1 head, L2 head, agreed L2 output root and L2 claim from the configured L1 and
+    /// L2 nodes if `--derive-inputs` is set, and leaves the configuration untouched otherwise.
+    ///
+    /// The claim is the output root of the L2 block at `--l2-block-number`, and the agreed output
+    /// root is that of its parent. The L1 head is set to the latest finalized L1 block, so that
+    /// the batches for the claimed block are available to derivation once it is safe.
+    pub async fn resolve_inputs(&mut self) -> Result<()> {
+        if !self.derive_inputs {
+            return Ok(());
+        }
+
+        if self.l2_block_number == 0 {
+            anyhow::bail!("Cannot derive inputs for the L2 genesis block");
+        }
+
+        let rpc_cache = self.construct_rpc_cache()?;
+        let l1_provider = util::http_provider(
+            self.l1_node_address.as_ref().ok_or_else(|| anyhow!("L1 node address must be set"))?,
+            rpc_cache.clone(),
+            "l1",
+        );
+        let l2_provider = util::http_provider(
+            self.l2_node_address.as_ref().ok_or_else(|| anyhow!("L2 node address must be set"))?,
+            rpc_cache,
+            "l2",
+        );
+
+        let claimed_hash =
+            block_hash(&l2_provider, BlockNumberOrTag::Number(self.l2_block_number)).await?;
+        let agreed_hash =
+            block_hash(&l2_provider, BlockNumberOrTag::Number(self.l2_block_number - 1)).await?;
+
+        self.l2_head = agreed_hash;
+        self.l2_output_root = util::fetch_output_root(&l2_provider, agreed_hash).await?.hash();
+        self.l2_claim = util::fetch_output_root(&l2_provider, claimed_hash).await?.hash();
+        self.l1_head = block_hash(&l1_provider, BlockNumberOrTag::Finalized).await?;
+
+        info!(
+            target: "kona_host",
+            "Derived inputs for L2 block #{}: l1_head={}, l2_head={}, l2_output_root={}, l2_claim={}",
+            self.l2_block_number,
+            self.l1_head,
+            self.l2_head,
+            self.l2_output_root,
+            self.l2_claim
+        );
+
+        Ok(())
+    }
+}
+
+/// Fetches the hash of the block identified by the given [BlockNumberOrTag].
+async fn block_hash(provider: &RpcProvider, block: BlockNumberOrTag) -> Result<B256> {
+    let block = provider
+        .get_block_by_number(block, false)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block {block}: {e}"))?
+        .ok_or_else(|| anyhow!("Block {block} not found"))?;
+    Ok(block.header.hash)
+}
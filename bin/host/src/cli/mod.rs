@@ -1,19 +1,32 @@
 //! This module contains all CLI-specific code for the host binary.
 
-use crate::kv::{
-    DiskKeyValueStore, LocalKeyValueStore, MemoryKeyValueStore, SharedKeyValueStore,
-    SplitKeyValueStore,
+use crate::{
+    kv::{
+        BlockingKeyValueStore, DiskKeyValueStore, EmbeddedKeyValueStore, LocalKeyValueStore,
+        MemoryKeyValueStore, SharedKeyValueStore, SplitKeyValueStore,
+    },
+    rpc_cache::{RpcCache, RpcCacheMode},
+    witness::Witness,
 };
 use alloy_primitives::B256;
 use anyhow::{anyhow, Result};
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, ValueEnum};
+use kona_preimage::transport::TransportAddr;
 use kona_primitives::RollupConfig;
 use serde::Serialize;
-use std::{path::PathBuf, sync::Arc};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
 
+mod config;
+pub use config::ChainPreset;
+
+mod inputs;
+
+mod sessions;
+pub use sessions::SessionInputs;
+
 mod parser;
-pub(crate) use parser::parse_b256;
+pub(crate) use parser::{parse_b256, parse_transport_addr};
 
 mod tracing_util;
 pub use tracing_util::init_tracing_subscriber;
@@ -24,36 +37,112 @@ pub struct HostCli {
     /// Verbosity level (0-4)
     #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
     pub v: u8,
+    /// Path to a TOML or JSON config file to read options from, keyed by their long flag names.
+    /// Options passed on the command line or through the environment take precedence over it.
+    #[clap(long, env = "KONA_HOST_CONFIG")]
+    pub config: Option<PathBuf>,
     /// Hash of the L1 head block. Derivation stops after this block is processed.
-    #[clap(long, value_parser = parse_b256)]
+    #[clap(
+        long,
+        value_parser = parse_b256,
+        required_unless_present_any = ["derive_inputs", "sessions"],
+        default_value_t = B256::ZERO,
+        hide_default_value = true
+    )]
     pub l1_head: B256,
     /// Hash of the L2 block at the L2 Output Root.
-    #[clap(long, value_parser = parse_b256)]
+    #[clap(
+        long,
+        value_parser = parse_b256,
+        required_unless_present_any = ["derive_inputs", "sessions"],
+        default_value_t = B256::ZERO,
+        hide_default_value = true
+    )]
     pub l2_head: B256,
     /// Agreed L2 Output Root to start derivation from.
-    #[clap(long, value_parser = parse_b256)]
+    #[clap(
+        long,
+        value_parser = parse_b256,
+        required_unless_present_any = ["derive_inputs", "sessions"],
+        default_value_t = B256::ZERO,
+        hide_default_value = true
+    )]
     pub l2_output_root: B256,
     /// Claimed L2 output root to validate
-    #[clap(long, value_parser = parse_b256)]
+    #[clap(
+        long,
+        value_parser = parse_b256,
+        required_unless_present_any = ["derive_inputs", "sessions"],
+        default_value_t = B256::ZERO,
+        hide_default_value = true
+    )]
     pub l2_claim: B256,
     /// Number of the L2 block that the claim is from.
-    #[clap(long)]
+    #[clap(
+        long,
+        required_unless_present = "sessions",
+        default_value_t = 0,
+        hide_default_value = true
+    )]
     pub l2_block_number: u64,
+    /// Derive `--l1-head`, `--l2-head`, `--l2-output-root` and `--l2-claim` from the L1 and L2
+    /// nodes before starting. The claim is the output root of `--l2-block-number`, the agreed
+    /// output root is that of its parent block, and the L1 head is the latest finalized L1 block.
+    #[clap(
+        long,
+        requires_all = ["l1_node_address", "l2_node_address"],
+        conflicts_with_all = ["l1_head", "l2_head", "l2_output_root", "l2_claim", "witness"]
+    )]
+    pub derive_inputs: bool,
+    /// Path to a JSON file listing the starting inputs of multiple client sessions. When set, one
+    /// client program is run natively per session, all served by this host with a shared fetcher
+    /// and preimage cache, while the boot information of each session is kept separate.
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "l1_head", "l2_head", "l2_output_root", "l2_claim", "l2_block_number", "derive_inputs",
+            "server", "witness", "export_witness"
+        ]
+    )]
+    pub sessions: Option<PathBuf>,
     /// The L2 chain ID.
-    #[clap(long)]
+    #[clap(
+        long,
+        required_unless_present = "chain",
+        default_value_t = 0,
+        hide_default_value = true
+    )]
     pub l2_chain_id: u64,
+    /// A chain in the superchain registry to run against. Sets the L2 chain ID, and serves the
+    /// chain's rollup config from the registry.
+    #[clap(long, value_enum, conflicts_with_all = ["l2_chain_id", "rollup_config_path"])]
+    pub chain: Option<ChainPreset>,
     /// Address of L2 JSON-RPC endpoint to use (eth and debug namespace required).
-    #[clap(long)]
+    #[clap(long, env = "KONA_HOST_L2_NODE_ADDRESS")]
     pub l2_node_address: Option<String>,
     /// Address of L1 JSON-RPC endpoint to use (eth namespace required)
-    #[clap(long)]
+    #[clap(long, env = "KONA_HOST_L1_NODE_ADDRESS")]
     pub l1_node_address: Option<String>,
     /// Address of the L1 Beacon API endpoint to use.
-    #[clap(long)]
+    #[clap(long, env = "KONA_HOST_L1_BEACON_ADDRESS")]
     pub l1_beacon_address: Option<String>,
     /// The Data Directory for preimage data storage. Default uses in-memory storage.
-    #[clap(long)]
+    #[clap(long, env = "KONA_HOST_DATA_DIR")]
     pub data_dir: Option<PathBuf>,
+    /// The storage backend to use for preimages within the `--data-dir`. Data directories written
+    /// by the `directory` backend must be migrated with `kona-host-migrate` before they can be
+    /// opened with the `embedded` backend.
+    #[clap(long, value_enum, default_value_t = KeyValueBackend::Embedded)]
+    pub kv_backend: KeyValueBackend,
+    /// Directory to record the raw responses of the L1, L2 and Beacon API endpoints in. With
+    /// `--rpc-cache-mode replay`, responses are served from the directory instead, without
+    /// network access.
+    #[clap(long, conflicts_with = "witness")]
+    pub rpc_cache: Option<PathBuf>,
+    /// Whether to record responses into the `--rpc-cache`, or replay them from it. In replay mode,
+    /// the endpoint addresses must still be set, but no requests are sent to them.
+    #[clap(long, value_enum, default_value_t = RpcCacheMode::Record, requires = "rpc_cache")]
+    pub rpc_cache_mode: RpcCacheMode,
     /// Run the specified client program as a separate process detached from the host. Default is
     /// to run the client program in the host process.
     #[clap(long)]
@@ -61,44 +150,118 @@ pub struct HostCli {
     /// Run in pre-image server mode without executing any client program. Defaults to `false`.
     #[clap(long)]
     pub server: bool,
-    /// Path to rollup config
+    /// Run as a long-lived pre-image server daemon that listens for client connections on the
+    /// given address, written as `unix:<path>` or `tcp:<ip>:<port>`. Each connection is served as
+    /// its own session, and all sessions share a single fetcher and preimage cache.
+    #[clap(
+        long,
+        value_parser = parse_transport_addr,
+        conflicts_with_all = ["server", "sessions", "exec", "export_witness"]
+    )]
+    #[serde(skip)]
+    pub listen: Option<TransportAddr>,
+    /// Path to rollup config. Required for chains outside of the superchain registry.
     #[clap(long)]
     pub rollup_config_path: Option<PathBuf>,
+    /// Path to write a witness file to, containing every preimage that the client program
+    /// requested during the run.
+    #[clap(long)]
+    pub export_witness: Option<PathBuf>,
+    /// Path to a witness file to serve preimages from. When set, the host runs offline and serves
+    /// all preimages, including the boot information, purely from the witness.
+    #[clap(long, conflicts_with_all = ["l1_node_address", "l2_node_address", "l1_beacon_address", "data_dir"])]
+    pub witness: Option<PathBuf>,
+    /// Path to write a JSON report to if the host runs offline and a preimage requested by the
+    /// client program is missing. The host exits with code 3 in that case, whether or not a
+    /// report is written.
+    #[clap(long)]
+    pub missing_preimage_report: Option<PathBuf>,
+    /// Address to serve Prometheus metrics on, written as `<ip>:<port>`. Metrics are served at
+    /// `/metrics`. If not set, the metrics server is not started.
+    #[clap(long)]
+    pub metrics_addr: Option<SocketAddr>,
 }
 
 impl HostCli {
     /// Returns `true` if the host is running in offline mode.
     pub fn is_offline(&self) -> bool {
-        self.l1_node_address.is_none() ||
+        self.witness.is_some() ||
+            self.l1_node_address.is_none() ||
             self.l2_node_address.is_none() ||
             self.l1_beacon_address.is_none()
     }
 
     /// Parses the CLI arguments and returns a new instance of a [SharedKeyValueStore], as it is
     /// configured to be created.
-    pub fn construct_kv_store(&self) -> SharedKeyValueStore {
+    pub fn construct_kv_store(&self) -> Result<SharedKeyValueStore> {
+        if let Some(ref witness_path) = self.witness {
+            let witness = Witness::read_from_file(witness_path)?;
+            return Ok(Arc::new(RwLock::new(witness)));
+        }
+
         let local_kv_store = LocalKeyValueStore::new(self.clone());
 
-        let kv_store: SharedKeyValueStore = if let Some(ref data_dir) = self.data_dir {
-            let disk_kv_store = DiskKeyValueStore::new(data_dir.clone());
-            let split_kv_store = SplitKeyValueStore::new(local_kv_store, disk_kv_store);
-            Arc::new(RwLock::new(split_kv_store))
-        } else {
-            let mem_kv_store = MemoryKeyValueStore::new();
-            let split_kv_store = SplitKeyValueStore::new(local_kv_store, mem_kv_store);
-            Arc::new(RwLock::new(split_kv_store))
+        let kv_store: SharedKeyValueStore = match (self.data_dir.as_ref(), self.kv_backend) {
+            (Some(data_dir), KeyValueBackend::Embedded) => {
+                let embedded_kv_store = EmbeddedKeyValueStore::open(data_dir.clone())?;
+                let split_kv_store = SplitKeyValueStore::new(local_kv_store, embedded_kv_store);
+                Arc::new(BlockingKeyValueStore::new(split_kv_store))
+            }
+            (Some(data_dir), KeyValueBackend::Directory) => {
+                let disk_kv_store = DiskKeyValueStore::new(data_dir.clone());
+                let split_kv_store = SplitKeyValueStore::new(local_kv_store, disk_kv_store);
+                Arc::new(BlockingKeyValueStore::new(split_kv_store))
+            }
+            (None, _) => {
+                let mem_kv_store = MemoryKeyValueStore::new();
+                let split_kv_store = SplitKeyValueStore::new(local_kv_store, mem_kv_store);
+                Arc::new(RwLock::new(split_kv_store))
+            }
+        };
+
+        Ok(kv_store)
+    }
+
+    /// Parses the CLI arguments and returns a new instance of a [SharedKeyValueStore] that only
+    /// holds globally-keyed preimages, for sharing between multiple client sessions. Boot
+    /// information is served per session by a [SessionKeyValueStore] wrapping the returned store.
+    ///
+    /// [SessionKeyValueStore]: crate::kv::SessionKeyValueStore
+    pub fn construct_shared_kv_store(&self) -> Result<SharedKeyValueStore> {
+        let kv_store: SharedKeyValueStore = match (self.data_dir.as_ref(), self.kv_backend) {
+            (Some(data_dir), KeyValueBackend::Embedded) => {
+                Arc::new(BlockingKeyValueStore::new(EmbeddedKeyValueStore::open(data_dir.clone())?))
+            }
+            (Some(data_dir), KeyValueBackend::Directory) => {
+                Arc::new(BlockingKeyValueStore::new(DiskKeyValueStore::new(data_dir.clone())))
+            }
+            (None, _) => Arc::new(RwLock::new(MemoryKeyValueStore::new())),
         };
 
-        kv_store
+        Ok(kv_store)
     }
 
-    /// Reads the [RollupConfig] from the file system and returns it as a string.
+    /// Returns the [RpcCache] that the host's providers record responses in or replay them from,
+    /// if `--rpc-cache` is set.
+    pub fn construct_rpc_cache(&self) -> Result<Option<Arc<RpcCache>>> {
+        self.rpc_cache
+            .as_ref()
+            .map(|dir| RpcCache::new(dir.clone(), self.rpc_cache_mode).map(Arc::new))
+            .transpose()
+    }
+
+    /// Reads the [RollupConfig] from the `--rollup-config-path`, or resolves it from the
+    /// superchain registry by the L2 chain ID if no path is given.
     pub fn read_rollup_config(&self) -> Result<RollupConfig> {
-        let path = self.rollup_config_path.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "No rollup config path provided. Please provide a path to the rollup config."
-            )
-        })?;
+        let Some(ref path) = self.rollup_config_path else {
+            return RollupConfig::from_l2_chain_id(self.l2_chain_id).ok_or_else(|| {
+                anyhow!(
+                    "No rollup config path provided, and L2 chain ID {} is not in the superchain \
+                     registry. Please provide a path to the rollup config.",
+                    self.l2_chain_id
+                )
+            });
+        };
 
         // Read the serialized config from the file system.
         let ser_config = std::fs::read_to_string(path)
@@ -109,3 +272,13 @@ impl HostCli {
             .map_err(|e| anyhow!("Error deserializing RollupConfig: {e}"))
     }
 }
+
+/// The storage backends available for preimages stored within the `--data-dir`.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyValueBackend {
+    /// An embedded, transactional database stored in a single file. See [EmbeddedKeyValueStore].
+    #[default]
+    Embedded,
+    /// One file per preimage, named by the hex-encoded key. See [DiskKeyValueStore].
+    Directory,
+}
@@ -1,4 +1,5 @@
 use alloy_primitives::B256;
+use kona_preimage::transport::TransportAddr;
 use std::str::FromStr;
 
 /// Parse string slices into alloy_primitives bytes
@@ -11,3 +12,14 @@ use std::str::FromStr;
 pub fn parse_b256(s: &str) -> Result<B256, String> {
     B256::from_str(s).map_err(|_| format!("Invalid B256 value: {}", s))
 }
+
+/// Parse string slices into a [TransportAddr]
+///
+/// # Arguments
+/// * `s` - string slice, written as `unix:<path>` or `tcp:<ip>:<port>`
+///
+/// # Returns
+/// * `Result<TransportAddr, String>` - Ok if successful, Err otherwise.
+pub fn parse_transport_addr(s: &str) -> Result<TransportAddr, String> {
+    TransportAddr::from_str(s).map_err(|e| e.to_string())
+}
@@ -0,0 +1,52 @@
+//! Contains the starting inputs of the client sessions served by a multi-session host.
+
+use super::HostCli;
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// The starting inputs of a single client session, as listed in the `--sessions` file.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInputs {
+    /// Hash of the L1 head block. Derivation stops after this block is processed.
+    pub l1_head: B256,
+    /// Hash of the L2 block at the L2 Output Root.
+    pub l2_head: B256,
+    /// Agreed L2 Output Root to start derivation from.
+    pub l2_output_root: B256,
+    /// Claimed L2 output root to validate.
+    pub l2_claim: B256,
+    /// Number of the L2 block that the claim is from.
+    pub l2_block_number: u64,
+}
+
+impl HostCli {
+    /// Reads the [SessionInputs] of all sessions from the `--sessions` file.
+    pub fn read_sessions(&self) -> Result<Vec<SessionInputs>> {
+        let path = self.sessions.as_ref().ok_or_else(|| anyhow!("No sessions file provided"))?;
+        let ser_sessions = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Error reading sessions file: {e}"))?;
+        let sessions: Vec<SessionInputs> = serde_json::from_str(&ser_sessions)
+            .map_err(|e| anyhow!("Error deserializing sessions file: {e}"))?;
+
+        if sessions.is_empty() {
+            anyhow::bail!("Sessions file does not list any sessions");
+        }
+        Ok(sessions)
+    }
+
+    /// Returns the configuration of a single session, with the starting inputs replaced by the
+    /// given [SessionInputs].
+    pub fn for_session(&self, inputs: &SessionInputs) -> Self {
+        Self {
+            l1_head: inputs.l1_head,
+            l2_head: inputs.l2_head,
+            l2_output_root: inputs.l2_output_root,
+            l2_claim: inputs.l2_claim,
+            l2_block_number: inputs.l2_block_number,
+            sessions: None,
+            ..self.clone()
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Contains the diagnostics that the host reports about the outcome of a session.
+//!
+//! The host exits with the [ExitCode] of the client program: `0` if the claim is valid, `1` if it
+//! is [InvalidClaim], and `2` if the client program or the host failed.
+//!
+//! In offline mode, the host can only serve the preimages that are already in its key-value store
+//! or witness. When the client program requests one that is not, the host records the missing
+//! key together with the last hint that preceded the request, and exits with
+//! [MISSING_PREIMAGE_EXIT_CODE]. This tells an incomplete witness apart from an invalid claim.
+//!
+//! [ExitCode]: kona_common::ExitCode
+
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use kona_preimage::{PreimageKey, PreimageKeyType};
+use serde::Serialize;
+use std::{fmt, path::Path};
+use tokio::sync::Mutex;
+
+/// The exit code of the host when an offline session failed because a preimage was missing.
+pub const MISSING_PREIMAGE_EXIT_CODE: i32 = 3;
+
+/// The outcome of a session in which the client program ran to completion, and found the claim to
+/// be invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidClaim;
+
+impl fmt::Display for InvalidClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The claim does not match the derived output root")
+    }
+}
+
+/// A preimage that an offline host could not serve to the client program.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingPreimage {
+    /// The requested preimage key, including its type byte.
+    pub key: B256,
+    /// The type of the requested preimage key.
+    pub key_type: PreimageKeyType,
+    /// The last hint that the client program sent before requesting the preimage, if any.
+    pub last_hint: Option<String>,
+}
+
+impl MissingPreimage {
+    /// Writes the [MissingPreimage] to the given path as a JSON report.
+    pub fn write_report(&self, path: &Path) -> Result<()> {
+        let report = serde_json::to_vec_pretty(self)
+            .map_err(|e| anyhow!("Failed to serialize missing preimage report: {e}"))?;
+        std::fs::write(path, report).map_err(|e| {
+            anyhow!("Failed to write missing preimage report to {}: {e}", path.display())
+        })
+    }
+}
+
+impl fmt::Display for MissingPreimage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing preimage for key {} (type {:?}), ", self.key, self.key_type)?;
+        match self.last_hint {
+            Some(ref hint) => write!(f, "last hint: {hint}"),
+            None => write!(f, "no preceding hint"),
+        }
+    }
+}
+
+/// Collects the hints received by an offline host and the first preimage that it could not serve,
+/// shared between its hint router and preimage fetcher.
+#[derive(Debug, Default)]
+pub struct OfflineDiagnostics {
+    /// The last hint received from the client program.
+    last_hint: Mutex<Option<String>>,
+    /// The first preimage that could not be served.
+    missing: Mutex<Option<MissingPreimage>>,
+}
+
+impl OfflineDiagnostics {
+    /// Records a hint received from the client program.
+    pub async fn record_hint(&self, hint: String) {
+        *self.last_hint.lock().await = Some(hint);
+    }
+
+    /// Records that the preimage for the given key could not be served, and returns the
+    /// [MissingPreimage]. Only the first missing preimage is retained.
+    pub async fn record_missing(&self, key: PreimageKey) -> MissingPreimage {
+        let missing = MissingPreimage {
+            key: key.into(),
+            key_type: key.key_type(),
+            last_hint: self.last_hint.lock().await.clone(),
+        };
+        self.missing.lock().await.get_or_insert_with(|| missing.clone());
+        missing
+    }
+
+    /// Returns the first preimage that could not be served, if any.
+    pub async fn missing(&self) -> Option<MissingPreimage> {
+        self.missing.lock().await.clone()
+    }
+}
@@ -0,0 +1,156 @@
+//! This module contains the [RpcBatcher], which coalesces concurrent JSON-RPC calls of a single
+//! method into batch requests, and the [StateBatcher], which batches the state trie node and proof
+//! requests made by the [HintHandler]s of one chain.
+//!
+//! [HintHandler]: super::HintHandler
+
+use crate::{rpc_cache::RpcProvider, util};
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_rpc_types::{BlockNumberOrTag, EIP1186AccountProofResponse};
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::oneshot;
+
+/// The maximum number of calls sent in a single batch request.
+const MAX_BATCH_SIZE: usize = 256;
+
+/// The calls queued by an [RpcBatcher], and whether a batch request is in flight.
+#[derive(Debug)]
+struct BatchQueue<P, R> {
+    /// The parameters of the queued calls, and the channels that their responses are sent on.
+    calls: Vec<(P, oneshot::Sender<Result<R, String>>)>,
+    /// Whether a task is currently sending the queued calls.
+    in_flight: bool,
+}
+
+/// Coalesces concurrent JSON-RPC calls of a single method into batch requests.
+///
+/// The first call is sent as soon as it is made. Calls made while a batch request is in flight are
+/// queued, and sent together in the next batch request once it completes. A burst of concurrent
+/// calls therefore costs two round trips, rather than one round trip per call.
+#[derive(Debug)]
+pub struct RpcBatcher<P, R> {
+    /// The provider that batch requests are sent to.
+    provider: RpcProvider,
+    /// The JSON-RPC method of the calls.
+    method: &'static str,
+    /// The queued calls.
+    queue: Arc<Mutex<BatchQueue<P, R>>>,
+}
+
+impl<P, R> Clone for RpcBatcher<P, R> {
+    fn clone(&self) -> Self {
+        Self { provider: self.provider.clone(), method: self.method, queue: self.queue.clone() }
+    }
+}
+
+impl<P, R> RpcBatcher<P, R>
+where
+    P: Serialize + Clone + Debug + Send + Sync + Unpin + 'static,
+    R: DeserializeOwned + Debug + Send + Sync + Unpin + 'static,
+{
+    /// Create a new [RpcBatcher] for calls of the given method to the given provider.
+    pub fn new(provider: RpcProvider, method: &'static str) -> Self {
+        Self {
+            provider,
+            method,
+            queue: Arc::new(Mutex::new(BatchQueue { calls: Vec::new(), in_flight: false })),
+        }
+    }
+
+    /// Calls the method with the given parameters, in a batch with any concurrent calls.
+    pub async fn call(&self, params: P) -> Result<R> {
+        let (sender, receiver) = oneshot::channel();
+        let send_batches = {
+            let mut queue = self.queue.lock().map_err(|e| anyhow!("Poisoned batch queue: {e}"))?;
+            queue.calls.push((params, sender));
+            !std::mem::replace(&mut queue.in_flight, true)
+        };
+
+        // The batches are sent from a separate task, so that they are not abandoned if the caller
+        // that started sending them is cancelled.
+        if send_batches {
+            tokio::task::spawn(self.clone().send_batches());
+        }
+
+        receiver
+            .await
+            .map_err(|_| anyhow!("Batched {} call was dropped", self.method))?
+            .map_err(|e| anyhow!(e))
+    }
+
+    /// Sends the queued calls in batch requests, until the queue is empty.
+    async fn send_batches(self) {
+        loop {
+            let calls = match self.queue.lock() {
+                Ok(mut queue) if queue.calls.is_empty() => {
+                    queue.in_flight = false;
+                    return;
+                }
+                Ok(mut queue) => {
+                    let count = queue.calls.len().min(MAX_BATCH_SIZE);
+                    queue.calls.drain(..count).collect::<Vec<_>>()
+                }
+                // Dropping the queued calls fails them.
+                Err(_) => return,
+            };
+
+            let (params, senders): (Vec<_>, Vec<_>) = calls.into_iter().unzip();
+            match util::batch_request::<P, R>(&self.provider, self.method, &params).await {
+                Ok(responses) => {
+                    for (sender, response) in senders.into_iter().zip(responses) {
+                        let _ = sender.send(Ok(response));
+                    }
+                }
+                Err(e) => {
+                    for sender in senders {
+                        let _ = sender.send(Err(e.to_string()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The parameters of an `eth_getProof` call: the account, its storage slots and the block.
+type ProofParams = (Address, Vec<B256>, BlockId);
+
+/// Batches the `debug_dbGet` and `eth_getProof` calls made for the state of a single chain.
+#[derive(Debug, Clone)]
+pub struct StateBatcher {
+    /// The batcher for `debug_dbGet` calls.
+    db_get: RpcBatcher<[B256; 1], Bytes>,
+    /// The batcher for `eth_getProof` calls.
+    get_proof: RpcBatcher<ProofParams, EIP1186AccountProofResponse>,
+}
+
+impl StateBatcher {
+    /// Create a new [StateBatcher] for calls to the given provider.
+    pub fn new(provider: RpcProvider) -> Self {
+        Self {
+            db_get: RpcBatcher::new(provider.clone(), "debug_dbGet"),
+            get_proof: RpcBatcher::new(provider, "eth_getProof"),
+        }
+    }
+
+    /// Fetches the preimage of the given hash, such as a state trie node, from the node's database.
+    pub async fn db_get(&self, hash: B256) -> Result<Bytes> {
+        self.db_get.call([hash]).await
+    }
+
+    /// Fetches the proof of an account and the given storage slots at the given block number.
+    pub async fn get_proof(
+        &self,
+        address: Address,
+        slots: Vec<B256>,
+        block_number: u64,
+    ) -> Result<EIP1186AccountProofResponse> {
+        let block = BlockId::Number(BlockNumberOrTag::Number(block_number));
+        self.get_proof.call((address, slots, block)).await
+    }
+}
@@ -0,0 +1,601 @@
+//! This module contains the built-in [HintHandler]s, one for each [HintType] that the client
+//! program may send.
+
+use super::{precompiles, Fetcher, HintHandler, HintHandlers};
+use crate::{kv::AsyncKeyValueStore, rpc_cache::RpcProvider, util};
+use alloy_eips::eip4844::FIELD_ELEMENTS_PER_BLOB;
+use alloy_primitives::{keccak256, Bytes, B256};
+use alloy_provider::Provider;
+use alloy_rpc_types::{Block, BlockTransactions, BlockTransactionsKind};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use kona_preimage::{PreimageKey, PreimageKeyType};
+use kona_primitives::{BlockInfo, Hint, HintType, IndexedBlobHash};
+use tracing::error;
+
+/// Decodes the data of a hint into the fields of the given [Hint] variant, returning them as a
+/// tuple (or as a single value, for variants with one field).
+macro_rules! decode_hint {
+    ($data:expr, $variant:ident { $($field:ident),+ }) => {
+        match Hint::decode_data(HintType::$variant, &$data).map_err(|e| anyhow!("{e}"))? {
+            Hint::$variant { $($field),+ } => ($($field),+),
+            hint => anyhow::bail!("Unexpected hint: {hint}"),
+        }
+    };
+}
+
+/// Registers the built-in [HintHandler] for every [HintType] into the given [HintHandlers].
+pub(crate) fn register_builtin<KV>(handlers: &mut HintHandlers<KV>)
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    handlers.register(HintType::L1BlockHeader, L1BlockHeaderHandler);
+    handlers.register(HintType::L1Transactions, L1TransactionsHandler);
+    handlers.register(HintType::L1Receipts, L1ReceiptsHandler);
+    handlers.register(HintType::L1Blob, L1BlobHandler);
+    handlers.register(HintType::L1Precompile, L1PrecompileHandler);
+    handlers.register(HintType::L1StateNode, L1StateNodeHandler);
+    handlers.register(HintType::L1AccountProof, L1AccountProofHandler);
+    handlers.register(HintType::L1AccountStorageProof, L1AccountStorageProofHandler);
+    handlers.register(HintType::L1Code, L1CodeHandler);
+    handlers.register(HintType::L2BlockHeader, L2BlockHeaderHandler);
+    handlers.register(HintType::L2Transactions, L2TransactionsHandler);
+    handlers.register(HintType::L2Code, L2CodeHandler);
+    handlers.register(HintType::StartingL2Output, StartingL2OutputHandler);
+    handlers.register(HintType::L2StateNode, L2StateNodeHandler);
+    handlers.register(HintType::L2AccountProof, L2AccountProofHandler);
+    handlers.register(HintType::L2AccountStorageProof, L2AccountStorageProofHandler);
+}
+
+/// Fetches the RLP-encoded header of an L1 block. Handles [HintType::L1BlockHeader] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1BlockHeaderHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1BlockHeaderHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the raw header from the L1 chain provider.
+        let hash = decode_hint!(hint_data, L1BlockHeader { hash });
+        let raw_header: Bytes = fetcher
+            .l1_provider()
+            .client()
+            .request("debug_getRawHeader", [hash])
+            .await
+            .map_err(|e| anyhow!(e))?;
+
+        // Set the preimage in the key-value store.
+        fetcher
+            .store_preimage(
+                PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
+                raw_header.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the transactions of an L1 block, and stores the nodes of its transactions trie. Handles
+/// [HintType::L1Transactions] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1TransactionsHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1TransactionsHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the block from the L1 chain provider and store the transactions within its
+        // body in the key-value store.
+        let hash = decode_hint!(hint_data, L1Transactions { hash });
+        let Block { transactions, .. } = fetcher
+            .l1_provider()
+            .get_block_by_hash(hash, BlockTransactionsKind::Full)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
+            .ok_or(anyhow!("Block not found."))?;
+        fetcher.store_transactions(transactions).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the receipts of an L1 block, and stores the nodes of its receipts trie. Handles
+/// [HintType::L1Receipts] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1ReceiptsHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1ReceiptsHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the receipts from the L1 chain provider and store the receipts within the
+        // key-value store.
+        let hash = decode_hint!(hint_data, L1Receipts { hash });
+        let raw_receipts: Vec<Bytes> = fetcher
+            .l1_provider()
+            .client()
+            .request("debug_getRawReceipts", [hash])
+            .await
+            .map_err(|e| anyhow!(e))?;
+        fetcher.store_trie_nodes(raw_receipts.as_slice()).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches a blob sidecar from the beacon node, and stores its commitment, field elements and KZG
+/// proof. Handles [HintType::L1Blob] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1BlobHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1BlobHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let (hash, index, timestamp) = decode_hint!(hint_data, L1Blob { hash, index, timestamp });
+
+        let partial_block_ref = BlockInfo { timestamp, ..Default::default() };
+        let indexed_hash = IndexedBlobHash { index: index as usize, hash };
+
+        // Fetch the blob sidecar from the blob provider.
+        let mut sidecars = fetcher
+            .blob_provider()
+            .fetch_filtered_sidecars(&partial_block_ref, std::slice::from_ref(&indexed_hash))
+            .await
+            .map_err(|e| anyhow!("Failed to fetch blob sidecars: {e}"))?;
+        if sidecars.len() != 1 {
+            anyhow::bail!("Expected 1 sidecar, got {}", sidecars.len());
+        }
+        let sidecar = sidecars.remove(0);
+
+        // Verify the blob against its versioned hash and KZG commitment before storing any
+        // of its field elements.
+        sidecar.verify_blob(&indexed_hash).map_err(|e| {
+            error!(target: "fetcher", "Rejecting blob sidecar for hash {hash}: {e}");
+            anyhow!("Invalid blob sidecar for hash {hash}: {e}")
+        })?;
+
+        // Set the preimage for the blob commitment.
+        let mut entries = Vec::with_capacity(2 * (FIELD_ELEMENTS_PER_BLOB as usize + 1) + 1);
+        entries.push((
+            PreimageKey::new(*hash, PreimageKeyType::Sha256).into(),
+            sidecar.kzg_commitment.to_vec(),
+        ));
+
+        // Write all the field elements to the key-value store. There should be 4096.
+        // The preimage oracle key for each field element is the keccak256 hash of
+        // `abi.encodePacked(sidecar.KZGCommitment, uint256(i))`
+        let mut blob_key = [0u8; 80];
+        blob_key[..48].copy_from_slice(sidecar.kzg_commitment.as_ref());
+        for i in 0..FIELD_ELEMENTS_PER_BLOB {
+            blob_key[72..].copy_from_slice(i.to_be_bytes().as_ref());
+            let blob_key_hash = keccak256(blob_key.as_ref());
+
+            entries.push((
+                PreimageKey::new(*blob_key_hash, PreimageKeyType::Keccak256).into(),
+                blob_key.into(),
+            ));
+            entries.push((
+                PreimageKey::new(*blob_key_hash, PreimageKeyType::Blob).into(),
+                sidecar.blob[(i as usize) << 5..(i as usize + 1) << 5].to_vec(),
+            ));
+        }
+
+        // Write the KZG Proof as the 4096th element.
+        blob_key[72..].copy_from_slice((FIELD_ELEMENTS_PER_BLOB).to_be_bytes().as_ref());
+        let blob_key_hash = keccak256(blob_key.as_ref());
+
+        entries.push((
+            PreimageKey::new(*blob_key_hash, PreimageKeyType::Keccak256).into(),
+            blob_key.into(),
+        ));
+        entries.push((
+            PreimageKey::new(*blob_key_hash, PreimageKeyType::Blob).into(),
+            sidecar.kzg_proof.to_vec(),
+        ));
+
+        // Set the preimages in the key-value store.
+        fetcher.store_preimages(entries).await?;
+
+        Ok(())
+    }
+}
+
+/// Executes an accelerated precompile call, and stores its input and result. Handles
+/// [HintType::L1Precompile] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1PrecompileHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1PrecompileHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the precompile address from the hint data.
+        let (address, input) = decode_hint!(hint_data, L1Precompile { address, input });
+        let input_hash = keccak256(hint_data.as_ref());
+
+        let result = match precompiles::execute(address, input.to_vec()) {
+            Ok(raw_res) => {
+                let mut res = Vec::with_capacity(1 + raw_res.len());
+                res.push(0x01); // success type byte
+                res.extend_from_slice(&raw_res);
+                res
+            }
+            Err(_) => {
+                // failure type byte
+                vec![0u8; 1]
+            }
+        };
+
+        // Set the preimages in the key-value store.
+        fetcher
+            .store_preimages(vec![
+                (
+                    PreimageKey::new(*input_hash, PreimageKeyType::Keccak256).into(),
+                    hint_data.into(),
+                ),
+                (PreimageKey::new(*input_hash, PreimageKeyType::Precompile).into(), result),
+            ])
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches a node of the L1 state trie by its hash. Handles [HintType::L1StateNode] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1StateNodeHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1StateNodeHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let hash = decode_hint!(hint_data, L1StateNode { hash });
+
+        // Fetch the preimage from the L1 chain provider, in a batch with concurrent requests.
+        let preimage = fetcher
+            .l1_state_batcher()
+            .db_get(hash)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))?;
+
+        fetcher
+            .store_preimage(
+                PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
+                preimage.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the proof of an account in the L1 state trie. Handles [HintType::L1AccountProof] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1AccountProofHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1AccountProofHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let (block_number, address) =
+            decode_hint!(hint_data, L1AccountProof { block_number, address });
+
+        let proof_response = fetcher
+            .l1_state_batcher()
+            .get_proof(address, Vec::new(), block_number)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+
+        // Write the account proof nodes to the key-value store.
+        fetcher.store_proof_nodes(proof_response.account_proof).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the proof of a storage slot of an account in the L1 state trie. Handles
+/// [HintType::L1AccountStorageProof] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1AccountStorageProofHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1AccountStorageProofHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let (block_number, address, slot) =
+            decode_hint!(hint_data, L1AccountStorageProof { block_number, address, slot });
+
+        let proof_response = fetcher
+            .l1_state_batcher()
+            .get_proof(address, vec![slot], block_number)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+
+        // Write the account and storage proof nodes to the key-value store.
+        let storage_proof = proof_response
+            .storage_proof
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Empty storage proof"))?;
+        let mut nodes = proof_response.account_proof;
+        nodes.extend(storage_proof.proof);
+        fetcher.store_proof_nodes(nodes).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the bytecode of an L1 contract by its code hash. Handles [HintType::L1Code] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1CodeHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L1CodeHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let hash = decode_hint!(hint_data, L1Code { hash });
+        let code = fetch_code(fetcher.l1_provider(), hash).await?;
+
+        fetcher
+            .store_preimage(PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(), code.into())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the RLP-encoded header of an L2 block. Handles [HintType::L2BlockHeader] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2BlockHeaderHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2BlockHeaderHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the raw header from the L2 chain provider.
+        let hash = decode_hint!(hint_data, L2BlockHeader { hash });
+        let raw_header: Bytes = fetcher
+            .l2_provider()
+            .client()
+            .request("debug_getRawHeader", [hash])
+            .await
+            .map_err(|e| anyhow!(e))?;
+
+        // Set the preimage in the key-value store.
+        fetcher
+            .store_preimage(
+                PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
+                raw_header.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the transactions of an L2 block, and stores the nodes of its transactions trie. Handles
+/// [HintType::L2Transactions] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2TransactionsHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2TransactionsHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        // Fetch the block from the L2 chain provider and store the transactions within its
+        // body in the key-value store.
+        let hash = decode_hint!(hint_data, L2Transactions { hash });
+        let Block { transactions, .. } = fetcher
+            .l2_provider()
+            .get_block_by_hash(hash, BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
+            .ok_or(anyhow!("Block not found."))?;
+
+        match transactions {
+            BlockTransactions::Hashes(transactions) => {
+                let params = transactions.into_iter().map(|hash| [hash]).collect::<Vec<_>>();
+                let encoded_transactions: Vec<Bytes> = util::batch_request(
+                    fetcher.l2_provider(),
+                    "debug_getRawTransaction",
+                    params.as_slice(),
+                )
+                .await
+                .map_err(|e| anyhow!("Error fetching transactions: {e}"))?;
+
+                fetcher.store_trie_nodes(encoded_transactions.as_slice()).await?;
+            }
+            _ => anyhow::bail!("Only BlockTransactions::Hashes are supported."),
+        };
+
+        Ok(())
+    }
+}
+
+/// Fetches the bytecode of an L2 contract by its code hash. Handles [HintType::L2Code] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2CodeHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2CodeHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let hash = decode_hint!(hint_data, L2Code { hash });
+        let code = fetch_code(fetcher.l2_provider(), hash).await?;
+
+        fetcher
+            .store_preimage(PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(), code.into())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Computes the output root of the session's L2 head, and stores its preimage. Handles
+/// [HintType::StartingL2Output] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StartingL2OutputHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for StartingL2OutputHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let expected = decode_hint!(hint_data, StartingL2Output { output_root });
+
+        let output = util::fetch_output_root(fetcher.l2_provider(), fetcher.l2_head()).await?;
+        let output_root = output.hash();
+
+        if output_root != expected {
+            anyhow::bail!("Output root does not match L2 head.");
+        }
+
+        fetcher
+            .store_preimage(
+                PreimageKey::new(*output_root, PreimageKeyType::Keccak256).into(),
+                output.encode(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches a node of the L2 state trie by its hash. Handles [HintType::L2StateNode] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2StateNodeHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2StateNodeHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let hash = decode_hint!(hint_data, L2StateNode { hash });
+
+        // Fetch the preimage from the L2 chain provider, in a batch with concurrent requests.
+        let preimage = fetcher
+            .l2_state_batcher()
+            .db_get(hash)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))?;
+
+        fetcher
+            .store_preimage(
+                PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
+                preimage.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the proof of an account in the L2 state trie. Handles [HintType::L2AccountProof] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2AccountProofHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2AccountProofHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let (block_number, address) =
+            decode_hint!(hint_data, L2AccountProof { block_number, address });
+
+        let proof_response = fetcher
+            .l2_state_batcher()
+            .get_proof(address, Vec::new(), block_number)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+
+        // Write the account proof nodes to the key-value store.
+        fetcher.store_proof_nodes(proof_response.account_proof).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the proof of a storage slot of an account in the L2 state trie. Handles
+/// [HintType::L2AccountStorageProof] hints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L2AccountStorageProofHandler;
+
+#[async_trait]
+impl<KV> HintHandler<KV> for L2AccountStorageProofHandler
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()> {
+        let (block_number, address, slot) =
+            decode_hint!(hint_data, L2AccountStorageProof { block_number, address, slot });
+
+        let proof_response = fetcher
+            .l2_state_batcher()
+            .get_proof(address, vec![slot], block_number)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+
+        // Write the account and storage proof nodes to the key-value store.
+        let storage_proof = proof_response
+            .storage_proof
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Empty storage proof"))?;
+        let mut nodes = proof_response.account_proof;
+        nodes.extend(storage_proof.proof);
+        fetcher.store_proof_nodes(nodes).await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the bytecode with the given code hash from the node's database.
+async fn fetch_code(provider: &RpcProvider, hash: B256) -> Result<Bytes> {
+    // geth hashdb scheme code hash key prefix
+    const CODE_PREFIX: u8 = b'c';
+
+    // Attempt to fetch the code with the geth hashdb scheme prefix.
+    let code_hash = [&[CODE_PREFIX], hash.as_slice()].concat();
+    let code =
+        provider.client().request::<&[Bytes; 1], Bytes>("debug_dbGet", &[code_hash.into()]).await;
+
+    // Check if the first attempt to fetch the code failed. If it did, try fetching the
+    // code hash preimage without the geth hashdb scheme prefix.
+    match code {
+        Ok(code) => Ok(code),
+        Err(_) => provider
+            .client()
+            .request::<&[B256; 1], Bytes>("debug_dbGet", &[hash])
+            .await
+            .map_err(|e| anyhow!("Error fetching code hash preimage: {e}")),
+    }
+}
@@ -1,514 +1,307 @@
 //! This module contains the [Fetcher] struct, which is responsible for fetching preimages from a
 //! remote source.
 
-use crate::{kv::KeyValueStore, util};
-use alloy_consensus::{Header, TxEnvelope};
-use alloy_eips::{eip2718::Encodable2718, eip4844::FIELD_ELEMENTS_PER_BLOB, BlockId};
-use alloy_primitives::{address, keccak256, Address, Bytes, B256};
-use alloy_provider::{Provider, ReqwestProvider};
-use alloy_rlp::Decodable;
-use alloy_rpc_types::{
-    Block, BlockNumberOrTag, BlockTransactions, BlockTransactionsKind, Transaction,
+use crate::{
+    kv::AsyncKeyValueStore,
+    metrics,
+    rpc_cache::{BeaconBlobProvider, RpcProvider},
+    util,
 };
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{keccak256, B256};
+use alloy_rpc_types::{BlockTransactions, Transaction};
 use anyhow::{anyhow, Result};
-use kona_client::HintType;
-use kona_derive::online::{OnlineBeaconClient, OnlineBlobProvider, SimpleSlotDerivation};
-use kona_preimage::{PreimageKey, PreimageKeyType};
-use kona_primitives::{BlockInfo, IndexedBlobHash};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::trace;
+use kona_preimage::{GlobalGenericHash, PreimageKey, PreimageKeyType};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{sync::Mutex, task::JoinHandle};
+use tracing::{error, trace, warn};
+
+mod batcher;
+pub use batcher::{RpcBatcher, StateBatcher};
+
+pub mod handlers;
 
 mod precompiles;
 
+mod registry;
+pub use registry::{HintHandler, HintHandlers};
+
+mod verify;
+
 /// The [Fetcher] struct is responsible for fetching preimages from a remote source.
 #[derive(Debug)]
 pub struct Fetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     /// Key-value store for preimages.
-    kv_store: Arc<RwLock<KV>>,
+    kv_store: Arc<KV>,
     /// L1 chain provider.
-    l1_provider: ReqwestProvider,
+    l1_provider: RpcProvider,
     /// The blob provider
-    blob_provider: OnlineBlobProvider<OnlineBeaconClient, SimpleSlotDerivation>,
+    blob_provider: BeaconBlobProvider,
     /// L2 chain provider.
     /// TODO: OP provider, N = Optimism
-    l2_provider: ReqwestProvider,
+    l2_provider: RpcProvider,
+    /// Batches the state requests made to the L1 chain provider.
+    l1_state_batcher: StateBatcher,
+    /// Batches the state requests made to the L2 chain provider.
+    l2_state_batcher: StateBatcher,
     /// L2 head
     l2_head: B256,
-    /// The last hint that was received. [None] if no hint has been received yet.
-    last_hint: Option<String>,
+    /// The handlers that hints are routed to, keyed by hint type.
+    handlers: Arc<HintHandlers<KV>>,
+    /// The hints received since the client last requested a preimage, in the order that they were
+    /// received. The client may send a burst of hints before requesting the preimages that they
+    /// make available, so all of them are retained until the next burst begins.
+    pending_hints: Vec<String>,
+    /// Whether a preimage has been requested since the last hint was received. If so, the next
+    /// hint begins a new burst and replaces the [Self::pending_hints].
+    hints_consumed: AtomicBool,
+    /// In-flight speculative prefetch tasks, keyed by the hint that spawned them.
+    prefetch_tasks: Arc<Mutex<HashMap<String, JoinHandle<Result<()>>>>>,
+}
+
+impl<KV> Clone for Fetcher<KV>
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    fn clone(&self) -> Self {
+        Self {
+            kv_store: self.kv_store.clone(),
+            l1_provider: self.l1_provider.clone(),
+            blob_provider: self.blob_provider.clone(),
+            l2_provider: self.l2_provider.clone(),
+            l1_state_batcher: self.l1_state_batcher.clone(),
+            l2_state_batcher: self.l2_state_batcher.clone(),
+            l2_head: self.l2_head,
+            handlers: self.handlers.clone(),
+            pending_hints: self.pending_hints.clone(),
+            hints_consumed: AtomicBool::new(self.hints_consumed.load(Ordering::Relaxed)),
+            prefetch_tasks: self.prefetch_tasks.clone(),
+        }
+    }
 }
 
 impl<KV> Fetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
 {
-    /// Create a new [Fetcher] with the given [KeyValueStore].
+    /// Create a new [Fetcher] with the given [AsyncKeyValueStore], routing hints to the built-in
+    /// [HintHandlers].
     pub fn new(
-        kv_store: Arc<RwLock<KV>>,
-        l1_provider: ReqwestProvider,
-        blob_provider: OnlineBlobProvider<OnlineBeaconClient, SimpleSlotDerivation>,
-        l2_provider: ReqwestProvider,
+        kv_store: Arc<KV>,
+        l1_provider: RpcProvider,
+        blob_provider: BeaconBlobProvider,
+        l2_provider: RpcProvider,
         l2_head: B256,
     ) -> Self {
-        Self { kv_store, l1_provider, blob_provider, l2_provider, l2_head, last_hint: None }
+        Self {
+            kv_store,
+            l1_state_batcher: StateBatcher::new(l1_provider.clone()),
+            l2_state_batcher: StateBatcher::new(l2_provider.clone()),
+            l1_provider,
+            blob_provider,
+            l2_provider,
+            l2_head,
+            handlers: Arc::new(HintHandlers::builtin()),
+            pending_hints: Vec::new(),
+            hints_consumed: AtomicBool::new(false),
+            prefetch_tasks: Default::default(),
+        }
     }
 
-    /// Set the last hint to be received.
-    pub fn hint(&mut self, hint: &str) {
+    /// Queue a received hint, and speculatively begin fetching its preimages in a background task
+    /// so that subsequent preimage requests can be served from the key-value store. Hints that
+    /// already have a prefetch task in flight are not fetched again.
+    pub async fn hint(&mut self, hint: &str) {
         trace!(target: "fetcher", "Received hint: {hint}");
-        self.last_hint = Some(hint.to_string());
-    }
-
-    /// Get the preimage for the given key.
-    pub async fn get_preimage(&self, key: B256) -> Result<Vec<u8>> {
-        const MAX_RETRIES: usize = 32;
-
-        trace!(target: "fetcher", "Pre-image requested. Key: {key}");
+        if self.hints_consumed.swap(false, Ordering::Relaxed) {
+            self.pending_hints.clear();
+        }
+        if !self.pending_hints.iter().any(|pending| pending == hint) {
+            self.pending_hints.push(hint.to_string());
+        }
 
-        // Acquire a read lock on the key-value store.
-        let kv_lock = self.kv_store.read().await;
-        let mut preimage = kv_lock.get(key);
+        let mut tasks = self.prefetch_tasks.lock().await;
 
-        // Drop the read lock before beginning the retry loop.
-        drop(kv_lock);
+        // Drop the handles of tasks that have already completed. Their preimages are either in
+        // the key-value store, or will be re-fetched in the foreground upon request.
+        tasks.retain(|_, task| !task.is_finished());
 
-        // Use a loop to keep retrying the prefetch as long as the key is not found
-        let mut retries = 0;
-        while preimage.is_none() && self.last_hint.is_some() {
-            if retries >= MAX_RETRIES {
-                tracing::error!(target: "fetcher", "Max retries exceeded.");
-                anyhow::bail!("Max retries exceeded.");
-            }
-
-            let hint = self.last_hint.as_ref().expect("Cannot be None");
-            self.prefetch(hint).await?;
+        if !tasks.contains_key(hint) {
+            let fetcher = self.clone();
+            let owned_hint = hint.to_string();
+            let task = tokio::task::spawn(async move { fetcher.prefetch(&owned_hint).await });
+            tasks.insert(hint.to_string(), task);
+        }
+    }
+}
 
-            let kv_lock = self.kv_store.read().await;
-            preimage = kv_lock.get(key);
+impl<KV> Fetcher<KV>
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    /// Registers a [HintHandler] for the given hint type, replacing the handler that was
+    /// previously registered for it, if any. Hint types that are unknown to [HintType] may be
+    /// registered, to handle custom hints sent by the client program.
+    pub fn with_handler<T, H>(mut self, hint_type: T, handler: H) -> Self
+    where
+        T: ToString,
+        H: HintHandler<KV> + 'static,
+    {
+        Arc::make_mut(&mut self.handlers).register(hint_type, handler);
+        self
+    }
 
-            retries += 1;
+    /// Create a [Fetcher] for a new client session. The session's [Fetcher] shares the providers,
+    /// hint handlers and in-flight prefetch tasks of this [Fetcher], but stores preimages into
+    /// the given key-value store and starts from the given L2 head.
+    pub fn for_session(&self, kv_store: Arc<KV>, l2_head: B256) -> Self {
+        Self {
+            kv_store,
+            l1_provider: self.l1_provider.clone(),
+            blob_provider: self.blob_provider.clone(),
+            l2_provider: self.l2_provider.clone(),
+            l1_state_batcher: self.l1_state_batcher.clone(),
+            l2_state_batcher: self.l2_state_batcher.clone(),
+            l2_head,
+            handlers: self.handlers.clone(),
+            pending_hints: Vec::new(),
+            hints_consumed: AtomicBool::new(false),
+            prefetch_tasks: self.prefetch_tasks.clone(),
         }
-
-        preimage.ok_or_else(|| anyhow!("Preimage not found."))
     }
 
-    /// Fetch the preimage for the given hint and insert it into the key-value store.
-    async fn prefetch(&self, hint: &str) -> Result<()> {
-        let (hint_type, hint_data) = util::parse_hint(hint)?;
-        trace!(target: "fetcher", "Fetching hint: {hint_type} {hint_data}");
+    /// Returns the L1 chain provider.
+    pub fn l1_provider(&self) -> &RpcProvider {
+        &self.l1_provider
+    }
 
-        match hint_type {
-            HintType::L1BlockHeader => {
-                // Validate the hint data length.
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Returns the L1 blob provider.
+    pub fn blob_provider(&self) -> &BeaconBlobProvider {
+        &self.blob_provider
+    }
 
-                // Fetch the raw header from the L1 chain provider.
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let raw_header: Bytes = self
-                    .l1_provider
-                    .client()
-                    .request("debug_getRawHeader", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
-
-                // Acquire a lock on the key-value store and set the preimage.
-                let mut kv_lock = self.kv_store.write().await;
-                kv_lock.set(
-                    PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
-                    raw_header.into(),
-                );
-            }
-            HintType::L1Transactions => {
-                // Validate the hint data length.
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Returns the L2 chain provider.
+    pub fn l2_provider(&self) -> &RpcProvider {
+        &self.l2_provider
+    }
 
-                // Fetch the block from the L1 chain provider and store the transactions within its
-                // body in the key-value store.
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let Block { transactions, .. } = self
-                    .l1_provider
-                    .get_block_by_hash(hash, BlockTransactionsKind::Full)
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
-                    .ok_or(anyhow!("Block not found."))?;
-                self.store_transactions(transactions).await?;
-            }
-            HintType::L1Receipts => {
-                // Validate the hint data length.
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Returns the [StateBatcher] for state requests made to the L1 chain provider.
+    pub fn l1_state_batcher(&self) -> &StateBatcher {
+        &self.l1_state_batcher
+    }
 
-                // Fetch the receipts from the L1 chain provider and store the receipts within the
-                // key-value store.
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let raw_receipts: Vec<Bytes> = self
-                    .l1_provider
-                    .client()
-                    .request("debug_getRawReceipts", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
-                self.store_trie_nodes(raw_receipts.as_slice()).await?;
-            }
-            HintType::L1Blob => {
-                if hint_data.len() != 48 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Returns the [StateBatcher] for state requests made to the L2 chain provider.
+    pub fn l2_state_batcher(&self) -> &StateBatcher {
+        &self.l2_state_batcher
+    }
 
-                let hash_data_bytes: [u8; 32] = hint_data[0..32]
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let index_data_bytes: [u8; 8] = hint_data[32..40]
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to u64: {e}"))?;
-                let timestamp_data_bytes: [u8; 8] = hint_data[40..48]
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to u64: {e}"))?;
-
-                let hash: B256 = hash_data_bytes.into();
-                let index = u64::from_be_bytes(index_data_bytes);
-                let timestamp = u64::from_be_bytes(timestamp_data_bytes);
-
-                let partial_block_ref = BlockInfo { timestamp, ..Default::default() };
-                let indexed_hash = IndexedBlobHash { index: index as usize, hash };
-
-                // Fetch the blob sidecar from the blob provider.
-                let mut sidecars = self
-                    .blob_provider
-                    .fetch_filtered_sidecars(&partial_block_ref, &[indexed_hash])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch blob sidecars: {e}"))?;
-                if sidecars.len() != 1 {
-                    anyhow::bail!("Expected 1 sidecar, got {}", sidecars.len());
-                }
-                let sidecar = sidecars.remove(0);
-
-                // Acquire a lock on the key-value store and set the preimages.
-                let mut kv_write_lock = self.kv_store.write().await;
-
-                // Set the preimage for the blob commitment.
-                kv_write_lock.set(
-                    PreimageKey::new(*hash, PreimageKeyType::Sha256).into(),
-                    sidecar.kzg_commitment.to_vec(),
-                );
-
-                // Write all the field elements to the key-value store. There should be 4096.
-                // The preimage oracle key for each field element is the keccak256 hash of
-                // `abi.encodePacked(sidecar.KZGCommitment, uint256(i))`
-                let mut blob_key = [0u8; 80];
-                blob_key[..48].copy_from_slice(sidecar.kzg_commitment.as_ref());
-                for i in 0..FIELD_ELEMENTS_PER_BLOB {
-                    blob_key[72..].copy_from_slice(i.to_be_bytes().as_ref());
-                    let blob_key_hash = keccak256(blob_key.as_ref());
-
-                    kv_write_lock.set(
-                        PreimageKey::new(*blob_key_hash, PreimageKeyType::Keccak256).into(),
-                        blob_key.into(),
-                    );
-                    kv_write_lock.set(
-                        PreimageKey::new(*blob_key_hash, PreimageKeyType::Blob).into(),
-                        sidecar.blob[(i as usize) << 5..(i as usize + 1) << 5].to_vec(),
-                    );
-                }
+    /// Returns the hash of the L2 head block of the session.
+    pub fn l2_head(&self) -> B256 {
+        self.l2_head
+    }
 
-                // Write the KZG Proof as the 4096th element.
-                blob_key[72..].copy_from_slice((FIELD_ELEMENTS_PER_BLOB).to_be_bytes().as_ref());
-                let blob_key_hash = keccak256(blob_key.as_ref());
-
-                kv_write_lock.set(
-                    PreimageKey::new(*blob_key_hash, PreimageKeyType::Keccak256).into(),
-                    blob_key.into(),
-                );
-                kv_write_lock.set(
-                    PreimageKey::new(*blob_key_hash, PreimageKeyType::Blob).into(),
-                    sidecar.kzg_proof.to_vec(),
-                );
-            }
-            HintType::L1Precompile => {
-                // Validate the hint data length.
-                if hint_data.len() < 20 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Get the preimage for the given key.
+    pub async fn get_preimage(&self, key: B256) -> Result<Vec<u8>> {
+        /// The maximum number of rounds in which the pending hints are fetched in the foreground.
+        const MAX_FETCH_ROUNDS: u32 = 6;
+        /// The delay before the first retry of the hints that failed to fetch. It doubles with
+        /// each subsequent retry.
+        const RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
-                // Fetch the precompile address from the hint data.
-                let precompile_address = Address::from_slice(&hint_data.as_ref()[..20]);
-                let precompile_input = hint_data[20..].to_vec();
-                let input_hash = keccak256(hint_data.as_ref());
-
-                let result = match precompiles::execute(precompile_address, precompile_input) {
-                    Ok(raw_res) => {
-                        let mut res = Vec::with_capacity(1 + raw_res.len());
-                        res.push(0x01); // success type byte
-                        res.extend_from_slice(&raw_res);
-                        res
-                    }
-                    Err(_) => {
-                        // failure type byte
-                        vec![0u8; 1]
+        trace!(target: "fetcher", "Pre-image requested. Key: {key}");
+        self.hints_consumed.store(true, Ordering::Relaxed);
+
+        let mut preimage = self.kv_store.get(key).await?;
+        let lookup_result = if preimage.is_some() { "hit" } else { "miss" };
+        metrics::KV_CACHE_LOOKUPS.with_label_values(&[lookup_result]).inc();
+
+        // If speculative prefetches for the pending hints are in flight, wait for them to complete
+        // before falling back to fetching the hints in the foreground.
+        if preimage.is_none() && !self.pending_hints.is_empty() {
+            let tasks = {
+                let mut tasks = self.prefetch_tasks.lock().await;
+                self.pending_hints.iter().filter_map(|hint| tasks.remove(hint)).collect::<Vec<_>>()
+            };
+            if !tasks.is_empty() {
+                for result in futures::future::join_all(tasks).await {
+                    match result {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => warn!(target: "fetcher", "Speculative prefetch failed: {e}"),
+                        Err(e) => warn!(target: "fetcher", "Speculative prefetch panicked: {e}"),
                     }
-                };
-
-                // Acquire a lock on the key-value store and set the preimages.
-                let mut kv_lock = self.kv_store.write().await;
-                kv_lock.set(
-                    PreimageKey::new(*input_hash, PreimageKeyType::Keccak256).into(),
-                    hint_data.into(),
-                );
-                kv_lock
-                    .set(PreimageKey::new(*input_hash, PreimageKeyType::Precompile).into(), result);
-            }
-            HintType::L2BlockHeader => {
-                // Validate the hint data length.
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
                 }
-
-                // Fetch the raw header from the L2 chain provider.
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let raw_header: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_getRawHeader", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
-
-                // Acquire a lock on the key-value store and set the preimage.
-                let mut kv_lock = self.kv_store.write().await;
-                kv_lock.set(
-                    PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
-                    raw_header.into(),
-                );
+                preimage = self.kv_store.get(key).await?;
             }
-            HintType::L2Transactions => {
-                // Validate the hint data length.
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+        }
 
-                // Fetch the block from the L2 chain provider and store the transactions within its
-                // body in the key-value store.
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let Block { transactions, .. } = self
-                    .l2_provider
-                    .get_block_by_hash(hash, BlockTransactionsKind::Hashes)
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
-                    .ok_or(anyhow!("Block not found."))?;
-
-                match transactions {
-                    BlockTransactions::Hashes(transactions) => {
-                        let mut encoded_transactions = Vec::with_capacity(transactions.len());
-                        for tx_hash in transactions {
-                            let tx = self
-                                .l2_provider
-                                .client()
-                                .request::<&[B256; 1], Bytes>("debug_getRawTransaction", &[tx_hash])
-                                .await
-                                .map_err(|e| anyhow!("Error fetching transaction: {e}"))?;
-                            encoded_transactions.push(tx);
-                        }
-
-                        self.store_trie_nodes(encoded_transactions.as_slice()).await?;
-                    }
-                    _ => anyhow::bail!("Only BlockTransactions::Hashes are supported."),
-                };
+        // Fetch the pending hints concurrently, and retry the hints that failed with an
+        // exponential backoff, as long as the key is not found. Hints that were fetched
+        // successfully are not fetched again.
+        let mut failed_hints = self.pending_hints.iter().collect::<Vec<_>>();
+        let mut round = 0;
+        while preimage.is_none() && !failed_hints.is_empty() {
+            if round > 0 {
+                metrics::FETCH_RETRIES.inc();
+                tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(round - 1)).await;
             }
-            HintType::L2Code => {
-                // geth hashdb scheme code hash key prefix
-                const CODE_PREFIX: u8 = b'c';
-
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
 
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-
-                // Attempt to fetch the code from the L2 chain provider.
-                let code_hash = [&[CODE_PREFIX], hash.as_slice()].concat();
-                let code = self
-                    .l2_provider
-                    .client()
-                    .request::<&[Bytes; 1], Bytes>("debug_dbGet", &[code_hash.into()])
+            let results =
+                futures::future::join_all(failed_hints.iter().map(|hint| self.prefetch(hint)))
                     .await;
-
-                // Check if the first attempt to fetch the code failed. If it did, try fetching the
-                // code hash preimage without the geth hashdb scheme prefix.
-                let code = match code {
-                    Ok(code) => code,
-                    Err(_) => self
-                        .l2_provider
-                        .client()
-                        .request::<&[B256; 1], Bytes>("debug_dbGet", &[hash])
-                        .await
-                        .map_err(|e| anyhow!("Error fetching code hash preimage: {e}"))?,
-                };
-
-                let mut kv_write_lock = self.kv_store.write().await;
-                kv_write_lock
-                    .set(PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(), code.into());
-            }
-            HintType::StartingL2Output => {
-                const OUTPUT_ROOT_VERSION: u8 = 0;
-                const L2_TO_L1_MESSAGE_PASSER_ADDRESS: Address =
-                    address!("4200000000000000000000000000000000000016");
-
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
-
-                // Fetch the header for the L2 head block.
-                let raw_header: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_getRawHeader", &[self.l2_head])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch header RLP: {e}"))?;
-                let header = Header::decode(&mut raw_header.as_ref())
-                    .map_err(|e| anyhow!("Failed to decode header: {e}"))?;
-
-                // Fetch the storage root for the L2 head block.
-                let l2_to_l1_message_passer = self
-                    .l2_provider
-                    .get_proof(L2_TO_L1_MESSAGE_PASSER_ADDRESS, Default::default())
-                    .block_id(BlockId::Hash(self.l2_head.into()))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
-
-                let mut raw_output = [0u8; 128];
-                raw_output[31] = OUTPUT_ROOT_VERSION;
-                raw_output[32..64].copy_from_slice(header.state_root.as_ref());
-                raw_output[64..96].copy_from_slice(l2_to_l1_message_passer.storage_hash.as_ref());
-                raw_output[96..128].copy_from_slice(self.l2_head.as_ref());
-                let output_root = keccak256(raw_output);
-
-                if output_root.as_slice() != hint_data.as_ref() {
-                    anyhow::bail!("Output root does not match L2 head.");
+            let mut last_error = None;
+            failed_hints = failed_hints
+                .into_iter()
+                .zip(results)
+                .filter_map(|(hint, result)| {
+                    let e = result.err()?;
+                    warn!(target: "fetcher", "Failed to fetch hint {hint}: {e}");
+                    last_error = Some(e);
+                    Some(hint)
+                })
+                .collect();
+
+            preimage = self.kv_store.get(key).await?;
+            round += 1;
+
+            if preimage.is_none() && round == MAX_FETCH_ROUNDS {
+                if let Some(e) = last_error {
+                    error!(target: "fetcher", "Max retries exceeded.");
+                    return Err(e);
                 }
-
-                let mut kv_write_lock = self.kv_store.write().await;
-                kv_write_lock.set(
-                    PreimageKey::new(*output_root, PreimageKeyType::Keccak256).into(),
-                    raw_output.into(),
-                );
             }
-            HintType::L2StateNode => {
-                if hint_data.len() != 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+        }
 
-                let hash: B256 = hint_data
-                    .as_ref()
-                    .try_into()
-                    .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-
-                // Fetch the preimage from the L2 chain provider.
-                let preimage: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_dbGet", &[hash])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))?;
-
-                let mut kv_write_lock = self.kv_store.write().await;
-                kv_write_lock.set(
-                    PreimageKey::new(*hash, PreimageKeyType::Keccak256).into(),
-                    preimage.into(),
-                );
-            }
-            HintType::L2AccountProof => {
-                if hint_data.len() != 8 + 20 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+        preimage.ok_or_else(|| anyhow!("Preimage not found."))
+    }
 
-                let block_number = u64::from_be_bytes(
-                    hint_data.as_ref()[..8]
-                        .try_into()
-                        .map_err(|e| anyhow!("Error converting hint data to u64: {e}"))?,
-                );
-                let address = Address::from_slice(&hint_data.as_ref()[8..28]);
-
-                let proof_response = self
-                    .l2_provider
-                    .get_proof(address, Default::default())
-                    .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
-
-                let mut kv_write_lock = self.kv_store.write().await;
-
-                // Write the account proof nodes to the key-value store.
-                proof_response.account_proof.into_iter().for_each(|node| {
-                    let node_hash = keccak256(node.as_ref());
-                    let key = PreimageKey::new(*node_hash, PreimageKeyType::Keccak256);
-                    kv_write_lock.set(key.into(), node.into());
-                });
-            }
-            HintType::L2AccountStorageProof => {
-                if hint_data.len() != 8 + 20 + 32 {
-                    anyhow::bail!("Invalid hint data length: {}", hint_data.len());
-                }
+    /// Fetch the preimage for the given hint and insert it into the key-value store.
+    async fn prefetch(&self, hint: &str) -> Result<()> {
+        let (hint_type, hint_data) = util::parse_hint(hint)?;
+        trace!(target: "fetcher", "Fetching hint: {hint_type} {hint_data}");
 
-                let block_number = u64::from_be_bytes(
-                    hint_data.as_ref()[..8]
-                        .try_into()
-                        .map_err(|e| anyhow!("Error converting hint data to u64: {e}"))?,
-                );
-                let address = Address::from_slice(&hint_data.as_ref()[8..28]);
-                let slot = B256::from_slice(&hint_data.as_ref()[28..]);
-
-                let mut proof_response = self
-                    .l2_provider
-                    .get_proof(address, vec![slot])
-                    .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
-
-                let mut kv_write_lock = self.kv_store.write().await;
-
-                // Write the account proof nodes to the key-value store.
-                proof_response.account_proof.into_iter().for_each(|node| {
-                    let node_hash = keccak256(node.as_ref());
-                    let key = PreimageKey::new(*node_hash, PreimageKeyType::Keccak256);
-                    kv_write_lock.set(key.into(), node.into());
-                });
-
-                // Write the storage proof nodes to the key-value store.
-                let storage_proof = proof_response.storage_proof.remove(0);
-                storage_proof.proof.into_iter().for_each(|node| {
-                    let node_hash = keccak256(node.as_ref());
-                    let key = PreimageKey::new(*node_hash, PreimageKeyType::Keccak256);
-                    kv_write_lock.set(key.into(), node.into());
-                });
-            }
-        }
+        let handler = self
+            .handlers
+            .get(hint_type)
+            .ok_or_else(|| anyhow!("No handler registered for hint type: {hint_type}"))?;
 
-        Ok(())
+        let timer = metrics::HINT_FETCH_LATENCY.with_label_values(&[hint_type]).start_timer();
+        let result = handler.fetch(self, hint_data).await;
+        timer.observe_duration();
+        if result.is_err() {
+            metrics::HINT_FETCH_ERRORS.with_label_values(&[hint_type]).inc();
+        }
+        result
     }
 
     /// Stores a list of [BlockTransactions] in the key-value store.
@@ -536,22 +329,58 @@ where
 
     /// Stores intermediate trie nodes in the key-value store. Assumes that all nodes passed are
     /// raw, RLP encoded trie nodes.
-    async fn store_trie_nodes<T: AsRef<[u8]>>(&self, nodes: &[T]) -> Result<()> {
+    pub async fn store_trie_nodes<T: AsRef<[u8]>>(&self, nodes: &[T]) -> Result<()> {
         let mut hb = kona_mpt::ordered_trie_with_encoder(nodes, |node, buf| {
             buf.put_slice(node.as_ref());
         });
         hb.root();
         let intermediates = hb.take_proofs();
 
-        let mut kv_write_lock = self.kv_store.write().await;
+        self.store_proof_nodes(intermediates.into_values()).await
+    }
 
-        for (_, value) in intermediates.into_iter() {
-            let value_hash = keccak256(value.as_ref());
-            let key = PreimageKey::new(*value_hash, PreimageKeyType::Keccak256);
+    /// Stores a set of raw trie nodes in the key-value store, keyed by their keccak256 hash. All
+    /// nodes are written as a single batch.
+    pub async fn store_proof_nodes<T, I>(&self, nodes: I) -> Result<()>
+    where
+        T: AsRef<[u8]> + Into<Vec<u8>>,
+        I: IntoIterator<Item = T>,
+    {
+        let entries = nodes
+            .into_iter()
+            .map(|node| {
+                let node_hash = keccak256(node.as_ref());
+                (PreimageKey::new(*node_hash, PreimageKeyType::Keccak256).into(), node.into())
+            })
+            .collect::<Vec<_>>();
+
+        self.store_preimages(entries).await
+    }
 
-            kv_write_lock.set(key.into(), value.into());
-        }
+    /// Stores a preimage in the key-value store under its [PreimageKeyType::GlobalGeneric] key for
+    /// the given [GlobalGenericHash], and returns the key. [HintHandler]s for new kinds of data
+    /// may use this to make their preimages available without a dedicated key type.
+    pub async fn store_global_generic(
+        &self,
+        hash: GlobalGenericHash,
+        value: Vec<u8>,
+    ) -> Result<B256> {
+        let key = PreimageKey::global_generic(hash, &value).into();
+        self.store_preimage(key, value).await?;
+        Ok(key)
+    }
+
+    /// Verifies a single preimage against its key, and stores it in the key-value store.
+    pub async fn store_preimage(&self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.store_preimages(vec![(key, value)]).await
+    }
 
-        Ok(())
+    /// Verifies a batch of preimages against their keys, and stores them in the key-value store.
+    /// If any preimage in the batch does not match its key, the whole batch is rejected.
+    pub async fn store_preimages(&self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        verify::verify_preimages(&entries).inspect_err(|e| {
+            error!(target: "fetcher", "Rejecting preimages from remote source: {e}");
+        })?;
+        self.kv_store.set_all(entries).await
     }
 }
@@ -10,8 +10,20 @@ use revm::{
 /// List of precompiles that are accelerated by the host program.
 pub(crate) const ACCELERATED_PRECOMPILES: &[PrecompileWithAddress] = &[
     precompile::secp256k1::ECRECOVER,                   // ecRecover
+    precompile::modexp::BERLIN,                         // modexp
+    precompile::bn128::add::ISTANBUL,                   // ecAdd
+    precompile::bn128::mul::ISTANBUL,                   // ecMul
     precompile::bn128::pair::ISTANBUL,                  // ecPairing
     precompile::kzg_point_evaluation::POINT_EVALUATION, // KZG point evaluation
+    precompile::bls12_381::g1_add::PRECOMPILE,          // BLS12-381 G1 add
+    precompile::bls12_381::g1_mul::PRECOMPILE,          // BLS12-381 G1 mul
+    precompile::bls12_381::g1_msm::PRECOMPILE,          // BLS12-381 G1 MSM
+    precompile::bls12_381::g2_add::PRECOMPILE,          // BLS12-381 G2 add
+    precompile::bls12_381::g2_mul::PRECOMPILE,          // BLS12-381 G2 mul
+    precompile::bls12_381::g2_msm::PRECOMPILE,          // BLS12-381 G2 MSM
+    precompile::bls12_381::pairing::PRECOMPILE,         // BLS12-381 pairing
+    precompile::bls12_381::map_fp_to_g1::PRECOMPILE,    // BLS12-381 map Fp to G1
+    precompile::bls12_381::map_fp2_to_g2::PRECOMPILE,   // BLS12-381 map Fp2 to G2
 ];
 
 /// Executes an accelerated precompile on [revm].
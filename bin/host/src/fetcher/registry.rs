@@ -0,0 +1,82 @@
+//! This module contains the [HintHandler] trait and the [HintHandlers] registry, which routes each
+//! hint to the handler registered for its type.
+
+use super::{handlers, Fetcher};
+use crate::kv::AsyncKeyValueStore;
+use alloy_primitives::Bytes;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+/// A [HintHandler] fetches the preimages for hints of a single type, and stores them in the
+/// key-value store through the [Fetcher].
+#[async_trait]
+pub trait HintHandler<KV>: Debug + Send + Sync
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    /// Fetches the preimages for a hint with the given data.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the preimages were fetched and stored.
+    /// - `Err(_)` if the hint data was invalid, or the preimages could not be fetched.
+    async fn fetch(&self, fetcher: &Fetcher<KV>, hint_data: Bytes) -> Result<()>;
+}
+
+/// A registry of [HintHandler]s, keyed by the hint type that they handle.
+#[derive(Debug)]
+pub struct HintHandlers<KV>
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    handlers: HashMap<String, Arc<dyn HintHandler<KV>>>,
+}
+
+impl<KV> Clone for HintHandlers<KV>
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    fn clone(&self) -> Self {
+        Self { handlers: self.handlers.clone() }
+    }
+}
+
+impl<KV> HintHandlers<KV>
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    /// Create a new [HintHandlers] registry with the built-in handler registered for every
+    /// [HintType].
+    ///
+    /// [HintType]: kona_primitives::HintType
+    pub fn builtin() -> Self {
+        let mut handlers = Self::empty();
+        handlers::register_builtin(&mut handlers);
+        handlers
+    }
+}
+
+impl<KV> HintHandlers<KV>
+where
+    KV: AsyncKeyValueStore + ?Sized,
+{
+    /// Create a new, empty [HintHandlers] registry.
+    pub fn empty() -> Self {
+        Self { handlers: HashMap::new() }
+    }
+
+    /// Registers a [HintHandler] for the given hint type, replacing any handler that was previously
+    /// registered for it.
+    pub fn register<T, H>(&mut self, hint_type: T, handler: H)
+    where
+        T: ToString,
+        H: HintHandler<KV> + 'static,
+    {
+        self.handlers.insert(hint_type.to_string(), Arc::new(handler));
+    }
+
+    /// Returns the [HintHandler] registered for the given hint type, if any.
+    pub fn get(&self, hint_type: &str) -> Option<&Arc<dyn HintHandler<KV>>> {
+        self.handlers.get(hint_type)
+    }
+}
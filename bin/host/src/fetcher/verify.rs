@@ -0,0 +1,47 @@
+//! Integrity checks for preimages fetched from remote sources.
+
+use alloy_primitives::{keccak256, B256};
+use anyhow::{anyhow, Result};
+use kona_preimage::{PreimageKey, PreimageKeyType};
+use sha2::{Digest, Sha256};
+
+/// Verifies that the given preimage matches its [PreimageKey]. Only [PreimageKeyType::Keccak256],
+/// [PreimageKeyType::Sha256] and [PreimageKeyType::GlobalGeneric] keys commit to their preimage;
+/// preimages of all other key types are accepted as-is.
+///
+/// ## Returns
+/// - `Ok(())`: If the preimage matches its key, or the key type does not commit to the preimage.
+/// - `Err(_)`: If the key is malformed, or the preimage does not hash to the key.
+pub(crate) fn verify_preimage(key: B256, preimage: &[u8]) -> Result<()> {
+    let key = PreimageKey::try_from(*key)?;
+    let expected = match key.key_type() {
+        PreimageKeyType::Keccak256 => PreimageKey::new(*keccak256(preimage), key.key_type()),
+        PreimageKeyType::Sha256 => {
+            PreimageKey::new(Sha256::digest(preimage).into(), key.key_type())
+        }
+        PreimageKeyType::GlobalGeneric => {
+            let hash = key
+                .global_generic_hash()
+                .ok_or_else(|| anyhow!("Unknown hash function for global generic key"))?;
+            PreimageKey::global_generic(hash, preimage)
+        }
+        _ => return Ok(()),
+    };
+
+    if expected != key {
+        anyhow::bail!(
+            "Preimage does not match its key; expected {}, got {}",
+            B256::from(expected),
+            B256::from(key)
+        );
+    }
+
+    Ok(())
+}
+
+/// Verifies all of the given preimages against their keys. See [verify_preimage].
+pub(crate) fn verify_preimages(entries: &[(B256, Vec<u8>)]) -> Result<()> {
+    entries.iter().try_for_each(|(key, preimage)| {
+        verify_preimage(*key, preimage).map_err(|e| anyhow!("Invalid preimage for key {key}: {e}"))
+    })
+}
@@ -0,0 +1,65 @@
+//! Contains an adapter that exposes a synchronous [KeyValueStore] as an [AsyncKeyValueStore] by
+//! running all operations on the blocking thread pool.
+
+use super::{AsyncKeyValueStore, KeyValueStore};
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+use tokio::task;
+
+/// An [AsyncKeyValueStore] adapter for synchronous [KeyValueStore]s that perform blocking I/O,
+/// such as the [DiskKeyValueStore]. Every operation is dispatched to tokio's blocking thread pool,
+/// so that slow reads and writes do not stall hint routing and preimage serving.
+///
+/// [DiskKeyValueStore]: super::DiskKeyValueStore
+#[derive(Debug)]
+pub struct BlockingKeyValueStore<KV>
+where
+    KV: KeyValueStore,
+{
+    inner: Arc<RwLock<KV>>,
+}
+
+impl<KV> BlockingKeyValueStore<KV>
+where
+    KV: KeyValueStore,
+{
+    /// Create a new [BlockingKeyValueStore] wrapping the given [KeyValueStore].
+    pub fn new(kv_store: KV) -> Self {
+        Self { inner: Arc::new(RwLock::new(kv_store)) }
+    }
+}
+
+#[async_trait]
+impl<KV> AsyncKeyValueStore for BlockingKeyValueStore<KV>
+where
+    KV: KeyValueStore + Send + Sync + 'static,
+{
+    async fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        let inner = Arc::clone(&self.inner);
+        task::spawn_blocking(move || {
+            inner.read().map_err(|_| anyhow!("Key-value store lock poisoned"))?.get(key)
+        })
+        .await
+        .map_err(|e| anyhow!("Key-value store task failed: {e}"))?
+    }
+
+    async fn set(&self, key: B256, value: Vec<u8>) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        task::spawn_blocking(move || {
+            inner.write().map_err(|_| anyhow!("Key-value store lock poisoned"))?.set(key, value)
+        })
+        .await
+        .map_err(|e| anyhow!("Key-value store task failed: {e}"))?
+    }
+
+    async fn set_all(&self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        task::spawn_blocking(move || {
+            inner.write().map_err(|_| anyhow!("Key-value store lock poisoned"))?.set_all(entries)
+        })
+        .await
+        .map_err(|e| anyhow!("Key-value store task failed: {e}"))?
+    }
+}
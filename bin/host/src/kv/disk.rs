@@ -4,8 +4,9 @@
 //! the value is the raw contents of the file.
 
 use super::KeyValueStore;
-use alloy_primitives::hex;
-use std::{fs, path::PathBuf};
+use alloy_primitives::{hex, B256};
+use anyhow::{anyhow, Result};
+use std::{fs, io::ErrorKind, path::PathBuf};
 
 /// A simple, synchronous key-value store that stores data on disk.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -18,18 +19,48 @@ impl DiskKeyValueStore {
     pub fn new(data_directory: PathBuf) -> Self {
         Self { data_directory }
     }
+
+    /// Returns all keys that are present in the store's data directory. Files that do not follow
+    /// the `<hex key>.bin` naming scheme are ignored.
+    pub fn keys(&self) -> Result<Vec<B256>> {
+        let entries = fs::read_dir(&self.data_directory)
+            .map_err(|e| anyhow!("Failed to read data directory: {e}"))?;
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| anyhow!("Failed to read directory entry: {e}"))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("bin") {
+                continue;
+            }
+
+            let key = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem).ok())
+                .filter(|raw| raw.len() == 32);
+            if let Some(key) = key {
+                keys.push(B256::from_slice(&key));
+            }
+        }
+
+        Ok(keys)
+    }
 }
 
 impl KeyValueStore for DiskKeyValueStore {
-    fn get(&self, key: alloy_primitives::B256) -> Option<Vec<u8>> {
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
         let path = self.data_directory.join(format!("{}.bin", hex::encode(key)));
-        fs::create_dir_all(&self.data_directory).ok()?;
-        fs::read(path).ok()
+        match fs::read(path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow!("Failed to read data from disk: {e}")),
+        }
     }
 
-    fn set(&mut self, key: alloy_primitives::B256, value: Vec<u8>) {
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()> {
         let path = self.data_directory.join(format!("{}.bin", hex::encode(key)));
-        fs::create_dir_all(&self.data_directory).expect("Failed to create directory");
-        fs::write(path, value.as_slice()).expect("Failed to write data to disk");
+        fs::create_dir_all(&self.data_directory)
+            .map_err(|e| anyhow!("Failed to create directory: {e}"))?;
+        fs::write(path, value.as_slice()).map_err(|e| anyhow!("Failed to write data to disk: {e}"))
     }
 }
@@ -0,0 +1,155 @@
+//! Contains a concrete implementation of the [KeyValueStore] trait that stores data in an embedded,
+//! transactional database.
+//!
+//! Data is stored in a single [redb] database file within the data directory, in one table that
+//! maps preimage keys to their preimages.
+
+use super::{DiskKeyValueStore, KeyValueStore};
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use redb::{Database, ReadableTableMetadata, TableDefinition};
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+/// The name of the database file within the data directory.
+const DATABASE_FILE_NAME: &str = "preimages.redb";
+
+/// The table that preimages are stored in, keyed by their 32-byte preimage key.
+const PREIMAGE_TABLE: TableDefinition<'_, &[u8; 32], &[u8]> = TableDefinition::new("preimages");
+
+/// The maximum number of entries that are written in a single transaction during a migration.
+const MIGRATION_BATCH_SIZE: usize = 4096;
+
+/// A key-value store that stores data in an embedded [redb] database. All writes are
+/// transactional, and batches of entries set through [KeyValueStore::set_all] are committed
+/// atomically.
+pub struct EmbeddedKeyValueStore {
+    data_directory: PathBuf,
+    db: Database,
+}
+
+impl core::fmt::Debug for EmbeddedKeyValueStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmbeddedKeyValueStore")
+            .field("data_directory", &self.data_directory)
+            .finish()
+    }
+}
+
+impl EmbeddedKeyValueStore {
+    /// Create a new [EmbeddedKeyValueStore] within the given data directory. The database file is
+    /// created if it does not already exist.
+    pub fn new(data_directory: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&data_directory)
+            .map_err(|e| anyhow!("Failed to create data directory: {e}"))?;
+        let db = Database::create(data_directory.join(DATABASE_FILE_NAME))
+            .map_err(|e| anyhow!("Failed to open database: {e}"))?;
+
+        // Create the preimage table up-front, so that reads never observe a missing table.
+        let tx = db.begin_write().map_err(|e| anyhow!("Failed to begin transaction: {e}"))?;
+        tx.open_table(PREIMAGE_TABLE).map_err(|e| anyhow!("Failed to open table: {e}"))?;
+        tx.commit().map_err(|e| anyhow!("Failed to commit transaction: {e}"))?;
+
+        Ok(Self { data_directory, db })
+    }
+
+    /// Opens the [EmbeddedKeyValueStore] within the given data directory, like [Self::new], but
+    /// refuses to create a new database within a directory that holds preimages in the
+    /// [DiskKeyValueStore] layout. Those preimages would not be visible to the new database, so
+    /// they must first be imported with `kona-host-migrate`.
+    pub fn open(data_directory: PathBuf) -> Result<Self> {
+        let has_database = data_directory.join(DATABASE_FILE_NAME).exists();
+        let has_legacy_preimages = DiskKeyValueStore::new(data_directory.clone())
+            .keys()
+            .is_ok_and(|keys| !keys.is_empty());
+        if !has_database && has_legacy_preimages {
+            anyhow::bail!(
+                "Data directory {dir} holds preimages written by the `directory` key-value \
+                 backend. Import them with `kona-host-migrate --from {dir} --data-dir {dir}`, or \
+                 pass `--kv-backend directory` to keep using them in place.",
+                dir = data_directory.display()
+            );
+        }
+
+        Self::new(data_directory)
+    }
+
+    /// Returns the number of preimages in the store.
+    pub fn len(&self) -> Result<u64> {
+        let tx = self.db.begin_read().map_err(|e| anyhow!("Failed to begin transaction: {e}"))?;
+        let table =
+            tx.open_table(PREIMAGE_TABLE).map_err(|e| anyhow!("Failed to open table: {e}"))?;
+        table.len().map_err(|e| anyhow!("Failed to read table length: {e}"))
+    }
+
+    /// Returns `true` if the store contains no preimages.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Imports all preimages from a [DiskKeyValueStore]'s directory layout into this store. Entries
+    /// are written in batches, each of which is committed atomically.
+    ///
+    /// ## Returns
+    /// - `Ok(n)`: The number of preimages that were imported.
+    /// - `Err(_)`: If the source directory could not be read, or the database could not be written.
+    pub fn import_from_disk(&mut self, disk_store: &DiskKeyValueStore) -> Result<usize> {
+        let keys = disk_store.keys()?;
+        let total = keys.len();
+
+        for (i, chunk) in keys.chunks(MIGRATION_BATCH_SIZE).enumerate() {
+            let entries = chunk
+                .iter()
+                .map(|key| {
+                    disk_store
+                        .get(*key)?
+                        .map(|value| (*key, value))
+                        .ok_or_else(|| anyhow!("Failed to read preimage for key {key}"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.write_batch(entries)?;
+
+            info!(
+                target: "kv",
+                "Migrated {} / {} preimages",
+                (i * MIGRATION_BATCH_SIZE + chunk.len()),
+                total
+            );
+        }
+
+        Ok(total)
+    }
+
+    /// Writes all given entries to the database within a single transaction.
+    fn write_batch(&mut self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        let tx = self.db.begin_write().map_err(|e| anyhow!("Failed to begin transaction: {e}"))?;
+        {
+            let mut table =
+                tx.open_table(PREIMAGE_TABLE).map_err(|e| anyhow!("Failed to open table: {e}"))?;
+            for (key, value) in entries.iter() {
+                table
+                    .insert(&key.0, value.as_slice())
+                    .map_err(|e| anyhow!("Failed to insert preimage: {e}"))?;
+            }
+        }
+        tx.commit().map_err(|e| anyhow!("Failed to commit transaction: {e}"))
+    }
+}
+
+impl KeyValueStore for EmbeddedKeyValueStore {
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        let tx = self.db.begin_read().map_err(|e| anyhow!("Failed to begin transaction: {e}"))?;
+        let table =
+            tx.open_table(PREIMAGE_TABLE).map_err(|e| anyhow!("Failed to open table: {e}"))?;
+        let value = table.get(&key.0).map_err(|e| anyhow!("Failed to read preimage: {e}"))?;
+        Ok(value.map(|v| v.value().to_vec()))
+    }
+
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.write_batch(vec![(key, value)])
+    }
+
+    fn set_all(&mut self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        self.write_batch(entries)
+    }
+}
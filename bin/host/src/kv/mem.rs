@@ -2,6 +2,7 @@
 
 use super::KeyValueStore;
 use alloy_primitives::B256;
+use anyhow::Result;
 use std::collections::HashMap;
 
 /// A simple, synchronous key-value store that stores data in memory. This is useful for testing and
@@ -19,11 +20,12 @@ impl MemoryKeyValueStore {
 }
 
 impl KeyValueStore for MemoryKeyValueStore {
-    fn get(&self, key: B256) -> Option<Vec<u8>> {
-        self.store.get(&key).cloned()
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        Ok(self.store.get(&key).cloned())
     }
 
-    fn set(&mut self, key: B256, value: Vec<u8>) {
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()> {
         self.store.insert(key, value);
+        Ok(())
     }
 }
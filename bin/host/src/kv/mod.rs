@@ -1,6 +1,9 @@
-//! This module contains the [KeyValueStore] trait and concrete implementations of it.
+//! This module contains the [KeyValueStore] and [AsyncKeyValueStore] traits and concrete
+//! implementations of them.
 
 use alloy_primitives::B256;
+use anyhow::Result;
+use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -10,20 +13,81 @@ pub use mem::MemoryKeyValueStore;
 mod disk;
 pub use disk::DiskKeyValueStore;
 
+mod embedded;
+pub use embedded::EmbeddedKeyValueStore;
+
 mod split;
 pub use split::SplitKeyValueStore;
 
 mod local;
 pub use local::LocalKeyValueStore;
 
+mod blocking;
+pub use blocking::BlockingKeyValueStore;
+
+mod session;
+pub use session::SessionKeyValueStore;
+
 /// A type alias for a shared key-value store.
-pub type SharedKeyValueStore = Arc<RwLock<dyn KeyValueStore + Send + Sync>>;
+pub type SharedKeyValueStore = Arc<dyn AsyncKeyValueStore + Send + Sync>;
 
 /// Describes the interface of a simple, synchronous key-value store.
 pub trait KeyValueStore {
     /// Get the value associated with the given key.
-    fn get(&self, key: B256) -> Option<Vec<u8>>;
+    ///
+    /// # Returns
+    /// - `Ok(Some(_))` if the key is present in the store.
+    /// - `Ok(None)` if the key is not present in the store.
+    /// - `Err(_)` if the store could not be read.
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>>;
 
     /// Set the value associated with the given key.
-    fn set(&mut self, key: B256, value: Vec<u8>);
+    ///
+    /// # Returns
+    /// - `Ok(())` if the value was written to the store.
+    /// - `Err(_)` if the store could not be written.
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()>;
+
+    /// Set the values associated with all of the given keys. Stores that support transactions
+    /// write the batch atomically.
+    fn set_all(&mut self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        for (key, value) in entries {
+            self.set(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes the interface of a key-value store that is accessed from async tasks. Implementations
+/// must not block the async runtime.
+#[async_trait]
+pub trait AsyncKeyValueStore {
+    /// Get the value associated with the given key. See [KeyValueStore::get].
+    async fn get(&self, key: B256) -> Result<Option<Vec<u8>>>;
+
+    /// Set the value associated with the given key. See [KeyValueStore::set].
+    async fn set(&self, key: B256, value: Vec<u8>) -> Result<()>;
+
+    /// Set the values associated with all of the given keys. See [KeyValueStore::set_all].
+    async fn set_all(&self, entries: Vec<(B256, Vec<u8>)>) -> Result<()>;
+}
+
+/// In-memory [KeyValueStore]s never block, and may be accessed directly from async tasks behind an
+/// async lock. Stores that perform I/O should be wrapped in a [BlockingKeyValueStore] instead.
+#[async_trait]
+impl<KV> AsyncKeyValueStore for RwLock<KV>
+where
+    KV: KeyValueStore + Send + Sync + ?Sized,
+{
+    async fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        self.read().await.get(key)
+    }
+
+    async fn set(&self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.write().await.set(key, value)
+    }
+
+    async fn set_all(&self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        self.write().await.set_all(entries)
+    }
 }
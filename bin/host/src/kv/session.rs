@@ -0,0 +1,47 @@
+//! Contains a concrete implementation of the [AsyncKeyValueStore] trait that isolates the boot
+//! information of a single client session from the preimages shared between sessions.
+
+use super::{AsyncKeyValueStore, KeyValueStore, LocalKeyValueStore, SharedKeyValueStore};
+use alloy_primitives::B256;
+use anyhow::Result;
+use async_trait::async_trait;
+use kona_preimage::PreimageKeyType;
+
+/// An [AsyncKeyValueStore] for a single client session. [PreimageKeyType::Local] keys are served
+/// from the session's own [LocalKeyValueStore], while all other keys are read from and written to
+/// a [SharedKeyValueStore] that may be shared with other sessions.
+pub struct SessionKeyValueStore {
+    local_store: LocalKeyValueStore,
+    shared_store: SharedKeyValueStore,
+}
+
+impl core::fmt::Debug for SessionKeyValueStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionKeyValueStore").field("local_store", &self.local_store).finish()
+    }
+}
+
+impl SessionKeyValueStore {
+    /// Create a new [SessionKeyValueStore] with the given session-local and shared stores.
+    pub fn new(local_store: LocalKeyValueStore, shared_store: SharedKeyValueStore) -> Self {
+        Self { local_store, shared_store }
+    }
+}
+
+#[async_trait]
+impl AsyncKeyValueStore for SessionKeyValueStore {
+    async fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        match PreimageKeyType::try_from(key[0])? {
+            PreimageKeyType::Local => self.local_store.get(key),
+            _ => self.shared_store.get(key).await,
+        }
+    }
+
+    async fn set(&self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.shared_store.set(key, value).await
+    }
+
+    async fn set_all(&self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        self.shared_store.set_all(entries).await
+    }
+}
@@ -2,12 +2,14 @@
 //! [KeyValueStore]s depending on [PreimageKeyType].
 
 use alloy_primitives::B256;
+use anyhow::Result;
 use kona_preimage::PreimageKeyType;
 
 use super::KeyValueStore;
 
 /// A split implementation of the [KeyValueStore] trait that splits between two separate
-/// [KeyValueStore]s.
+/// [KeyValueStore]s. [PreimageKeyType::Local] keys are read from the local store, and all global
+/// key types, including [PreimageKeyType::GlobalGeneric], from the remote store.
 #[derive(Clone, Debug)]
 pub struct SplitKeyValueStore<L, R>
 where
@@ -34,14 +36,22 @@ where
     L: KeyValueStore,
     R: KeyValueStore,
 {
-    fn get(&self, key: B256) -> Option<Vec<u8>> {
-        match PreimageKeyType::try_from(key[0]).ok()? {
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        match PreimageKeyType::try_from(key[0])? {
             PreimageKeyType::Local => self.local_store.get(key),
-            _ => self.remote_store.get(key),
+            PreimageKeyType::Keccak256 |
+            PreimageKeyType::GlobalGeneric |
+            PreimageKeyType::Sha256 |
+            PreimageKeyType::Blob |
+            PreimageKeyType::Precompile => self.remote_store.get(key),
         }
     }
 
-    fn set(&mut self, key: B256, value: Vec<u8>) {
-        self.remote_store.set(key, value);
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.remote_store.set(key, value)
+    }
+
+    fn set_all(&mut self, entries: Vec<(B256, Vec<u8>)>) -> Result<()> {
+        self.remote_store.set_all(entries)
     }
 }
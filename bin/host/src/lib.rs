@@ -5,32 +5,43 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
 pub mod cli;
+pub mod diagnostics;
 pub mod fetcher;
 pub mod kv;
+pub mod metrics;
 pub mod preimage;
+pub mod rpc_cache;
 pub mod server;
 pub mod util;
+pub mod witness;
 
-pub use cli::{init_tracing_subscriber, HostCli};
+pub use cli::{init_tracing_subscriber, HostCli, KeyValueBackend};
+use diagnostics::InvalidClaim;
 use fetcher::Fetcher;
 use server::PreimageServer;
 
 use anyhow::{anyhow, Result};
 use command_fds::{CommandFdExt, FdMapping};
 use futures::FutureExt;
-use kona_common::FileDescriptor;
-use kona_derive::online::{OnlineBeaconClient, OnlineBlobProvider};
-use kona_preimage::{HintReader, OracleServer, PipeHandle};
-use kv::KeyValueStore;
+use kona_common::{ExitCode, FileDescriptor};
+use kona_derive::online::OnlineBlobProvider;
+use kona_preimage::{
+    transport::{StreamTransport, TransportListener},
+    HintReader, HintReaderServer, OracleServer, PipeHandle, PreimageOracleServer,
+};
+use kv::{AsyncKeyValueStore, LocalKeyValueStore, SessionKeyValueStore, SharedKeyValueStore};
+use rpc_cache::RpcCacheBeaconClient;
 use std::{
     io::{stderr, stdin, stdout},
     os::fd::{AsFd, AsRawFd},
     panic::AssertUnwindSafe,
+    path::Path,
     sync::Arc,
 };
 use tokio::{process::Command, sync::RwLock, task};
 use tracing::{error, info};
 use util::Pipe;
+use witness::Witness;
 
 /// Starts the [PreimageServer] in the primary thread. In this mode, the host program has been
 /// invoked by the Fault Proof VM and the client program is running in the parent process.
@@ -40,39 +51,24 @@ pub async fn start_server(cfg: HostCli) -> Result<()> {
         PipeHandle::new(FileDescriptor::HintRead, FileDescriptor::HintWrite),
     );
     let oracle_server = OracleServer::new(preimage_pipe);
-    let hint_reader = HintReader::new(hint_pipe);
-
-    let kv_store = cfg.construct_kv_store();
-
-    let fetcher = if !cfg.is_offline() {
-        let beacon_client = OnlineBeaconClient::new_http(
-            cfg.l1_beacon_address.clone().expect("Beacon API URL must be set"),
-        );
-        let mut blob_provider = OnlineBlobProvider::new(beacon_client, None, None);
-        blob_provider
-            .load_configs()
-            .await
-            .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))?;
-        let l1_provider = util::http_provider(&cfg.l1_node_address.expect("Provider must be set"));
-        let l2_provider = util::http_provider(&cfg.l2_node_address.expect("Provider must be set"));
-        Some(Arc::new(RwLock::new(Fetcher::new(
-            kv_store.clone(),
-            l1_provider,
-            blob_provider,
-            l2_provider,
-            cfg.l2_head,
-        ))))
-    } else {
-        None
-    };
+    let hint_reader = HintReader::new(hint_pipe).with_batch_requests(true);
+
+    let kv_store = cfg.construct_kv_store()?;
+
+    let fetcher =
+        construct_fetcher(&cfg, kv_store.clone()).await?.map(|f| Arc::new(RwLock::new(f)));
 
     // Start the server and wait for it to complete.
     info!("Starting preimage server.");
-    let server = PreimageServer::new(oracle_server, hint_reader, kv_store, fetcher);
-    server.start().await?;
+    let witness = cfg.export_witness.is_some().then(|| Arc::new(RwLock::new(Witness::new())));
+    let mut server = PreimageServer::new(oracle_server, hint_reader, kv_store, fetcher);
+    if let Some(ref witness) = witness {
+        server = server.with_witness(witness.clone());
+    }
+    let result = server.start().await;
     info!("Preimage server has exited.");
 
-    Ok(())
+    export_witness(cfg.export_witness.as_deref(), witness, result).await
 }
 
 /// Starts the [PreimageServer] and the client program in separate threads. The client program is
@@ -81,76 +77,261 @@ pub async fn start_server_and_native_client(cfg: HostCli) -> Result<()> {
     let hint_pipe = util::bidirectional_pipe()?;
     let preimage_pipe = util::bidirectional_pipe()?;
 
-    let kv_store = cfg.construct_kv_store();
-
-    let fetcher = if !cfg.is_offline() {
-        let beacon_client = OnlineBeaconClient::new_http(
-            cfg.l1_beacon_address.clone().expect("Beacon API URL must be set"),
-        );
-        let mut blob_provider = OnlineBlobProvider::new(beacon_client, None, None);
-        blob_provider
-            .load_configs()
-            .await
-            .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))?;
-        let l1_provider =
-            util::http_provider(cfg.l1_node_address.as_ref().expect("Provider must be set"));
-        let l2_provider =
-            util::http_provider(cfg.l2_node_address.as_ref().expect("Provider must be set"));
-        Some(Arc::new(RwLock::new(Fetcher::new(
-            kv_store.clone(),
-            l1_provider,
-            blob_provider,
-            l2_provider,
-            cfg.l2_head,
-        ))))
-    } else {
-        None
-    };
+    let kv_store = cfg.construct_kv_store()?;
+
+    let fetcher =
+        construct_fetcher(&cfg, kv_store.clone()).await?.map(|f| Arc::new(RwLock::new(f)));
 
     // Create the server and start it.
+    let witness = cfg.export_witness.is_some().then(|| Arc::new(RwLock::new(Witness::new())));
     let server_task = task::spawn(start_native_preimage_server(
         kv_store,
         fetcher,
+        witness.clone(),
         hint_pipe.host,
         preimage_pipe.host,
     ));
 
     // Start the client program in a separate child process.
+    let witness_path = cfg.export_witness.clone();
     let program_task =
         task::spawn(start_native_client_program(cfg, hint_pipe.client, preimage_pipe.client));
 
     // Execute both tasks and wait for them to complete.
     info!("Starting preimage server and client program.");
-    tokio::select!(
-        r = util::flatten_join_result(server_task) => r?,
-        r = util::flatten_join_result(program_task) => r?
-    );
+    let result = wait_for_session(server_task, program_task).await;
     info!(target: "kona_host", "Preimage server and client program have exited.");
 
+    export_witness(witness_path.as_deref(), witness, result).await
+}
+
+/// Starts one client program natively per session listed in the `--sessions` file. Each session
+/// is served by its own [PreimageServer] over its own pair of pipes, and all sessions share a
+/// single [Fetcher] and preimage cache. The boot information of each session is served from a
+/// [SessionKeyValueStore], so that sessions never observe each other's starting inputs.
+///
+/// ## Returns
+/// - `Ok(())` if all sessions completed successfully.
+/// - `Err(_)` if any session failed.
+pub async fn start_server_and_native_clients(cfg: HostCli) -> Result<()> {
+    let sessions = cfg.read_sessions()?;
+    let shared_kv_store = cfg.construct_shared_kv_store()?;
+    let fetcher = construct_fetcher(&cfg, shared_kv_store.clone()).await?;
+
+    info!(target: "kona_host", "Starting {} client sessions.", sessions.len());
+    let mut session_tasks = Vec::with_capacity(sessions.len());
+    for inputs in sessions.iter() {
+        let session_cfg = cfg.for_session(inputs);
+        let kv_store: SharedKeyValueStore = Arc::new(SessionKeyValueStore::new(
+            LocalKeyValueStore::new(session_cfg.clone()),
+            shared_kv_store.clone(),
+        ));
+        let fetcher = fetcher
+            .as_ref()
+            .map(|f| Arc::new(RwLock::new(f.for_session(kv_store.clone(), inputs.l2_head))));
+
+        let hint_pipe = util::bidirectional_pipe()?;
+        let preimage_pipe = util::bidirectional_pipe()?;
+        let server_task = task::spawn(start_native_preimage_server(
+            kv_store,
+            fetcher,
+            None,
+            hint_pipe.host,
+            preimage_pipe.host,
+        ));
+        let program_task = task::spawn(start_native_client_program(
+            session_cfg,
+            hint_pipe.client,
+            preimage_pipe.client,
+        ));
+        session_tasks.push(wait_for_session(server_task, program_task));
+    }
+
+    let results = futures::future::join_all(session_tasks).await;
+    let mut failed = 0;
+    for (inputs, result) in sessions.iter().zip(results) {
+        match result {
+            Ok(()) => info!(
+                target: "kona_host",
+                "Session for L2 block #{} completed successfully.",
+                inputs.l2_block_number
+            ),
+            Err(e) => {
+                error!(
+                    target: "kona_host",
+                    "Session for L2 block #{} failed: {e:#}",
+                    inputs.l2_block_number
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} sessions failed", sessions.len());
+    }
     Ok(())
 }
 
+/// Starts the host as a long-lived daemon, serving preimages to clients that connect to the
+/// `--listen` address. Each connection is served as its own session by a [PreimageServer] over a
+/// [StreamTransport], and all sessions share a single [Fetcher] and preimage cache. The daemon runs
+/// until the process is terminated.
+///
+/// Every session is served the boot information given on the command line, so the daemon serves a
+/// single claim. Clients that expect another claim must reject the session, as the `kona` client
+/// program does when its `KONA_L2_CLAIM` does not match the served claim.
+pub async fn start_server_daemon(cfg: HostCli) -> Result<()> {
+    let addr = cfg.listen.clone().ok_or_else(|| anyhow!("No listen address specified."))?;
+    let listener = Arc::new(TransportListener::bind(&addr)?);
+
+    let kv_store = cfg.construct_kv_store()?;
+    let fetcher = construct_fetcher(&cfg, kv_store.clone()).await?;
+
+    info!(
+        target: "kona_host",
+        "Listening for client connections on {addr}, serving L2 claim {}.",
+        cfg.l2_claim
+    );
+    let mut session_id = 0;
+    loop {
+        let (transport, peer) = match task::spawn_blocking({
+            let listener = listener.clone();
+            move || listener.accept()
+        })
+        .await
+        .map_err(|e| anyhow!("Listener task failed: {e}"))?
+        {
+            Ok(connection) => connection,
+            Err(e) => {
+                error!(target: "kona_host", "{e}");
+                continue;
+            }
+        };
+
+        info!(target: "kona_host", "Accepted client connection #{session_id} from {peer}.");
+        let fetcher = fetcher
+            .as_ref()
+            .map(|f| Arc::new(RwLock::new(f.for_session(kv_store.clone(), cfg.l2_head))));
+        task::spawn(start_transport_preimage_server(
+            kv_store.clone(),
+            fetcher,
+            transport,
+            session_id,
+        ));
+        session_id += 1;
+    }
+}
+
+/// Constructs the [Fetcher] for the host, if it is running in online mode.
+async fn construct_fetcher<KV>(cfg: &HostCli, kv_store: Arc<KV>) -> Result<Option<Fetcher<KV>>>
+where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    if cfg.is_offline() {
+        return Ok(None);
+    }
+
+    let (Some(l1_node_address), Some(l2_node_address), Some(l1_beacon_address)) =
+        (cfg.l1_node_address.as_ref(), cfg.l2_node_address.as_ref(), cfg.l1_beacon_address.clone())
+    else {
+        anyhow::bail!("The L1, L2 and beacon endpoints must all be set to run online");
+    };
+
+    let rpc_cache = cfg.construct_rpc_cache()?;
+    let beacon_client = RpcCacheBeaconClient::new(l1_beacon_address, rpc_cache.clone());
+    let mut blob_provider = OnlineBlobProvider::new(beacon_client, None, None);
+    blob_provider
+        .load_configs()
+        .await
+        .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))?;
+    let l1_provider = util::http_provider(l1_node_address, rpc_cache.clone(), "l1");
+    let l2_provider = util::http_provider(l2_node_address, rpc_cache, "l2");
+
+    Ok(Some(Fetcher::new(kv_store, l1_provider, blob_provider, l2_provider, cfg.l2_head)))
+}
+
+/// Waits for both the preimage server and the client program of a native session to exit. The
+/// server exits once the client program closes its end of the pipes, so the outcome of the session
+/// is only known once both have exited.
+async fn wait_for_session(
+    server_task: task::JoinHandle<Result<()>>,
+    program_task: task::JoinHandle<Result<()>>,
+) -> Result<()> {
+    let (server, program) = tokio::join!(
+        util::flatten_join_result(server_task),
+        util::flatten_join_result(program_task)
+    );
+    server.and(program)
+}
+
 /// Starts the preimage server in a separate thread. The client program is ran natively in this
 /// mode.
 pub async fn start_native_preimage_server<KV>(
-    kv_store: Arc<RwLock<KV>>,
+    kv_store: Arc<KV>,
     fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+    witness: Option<Arc<RwLock<Witness>>>,
     hint_pipe: Pipe,
     preimage_pipe: Pipe,
 ) -> Result<()>
 where
-    KV: KeyValueStore + Send + Sync + ?Sized + 'static,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
 {
     let hint_reader = HintReader::new(PipeHandle::new(
         FileDescriptor::Wildcard(hint_pipe.read.as_raw_fd() as usize),
         FileDescriptor::Wildcard(hint_pipe.write.as_raw_fd() as usize),
-    ));
+    ))
+    .with_batch_requests(true);
     let oracle_server = OracleServer::new(PipeHandle::new(
         FileDescriptor::Wildcard(preimage_pipe.read.as_raw_fd() as usize),
         FileDescriptor::Wildcard(preimage_pipe.write.as_raw_fd() as usize),
     ));
 
-    let server = PreimageServer::new(oracle_server, hint_reader, kv_store, fetcher);
+    serve_preimages(oracle_server, hint_reader, kv_store, fetcher, witness).await
+}
+
+/// Serves a single client connection of the daemon over its [StreamTransport], and closes the
+/// connection once the session ends.
+async fn start_transport_preimage_server<KV>(
+    kv_store: Arc<KV>,
+    fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+    transport: StreamTransport,
+    session_id: u64,
+) where
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    let hint_reader = HintReader::new(transport.hint_channel()).with_batch_requests(true);
+    let oracle_server = OracleServer::new(transport.preimage_channel());
+
+    match serve_preimages(oracle_server, hint_reader, kv_store, fetcher, None).await {
+        Ok(()) => info!(target: "kona_host", "Session #{session_id} completed."),
+        Err(e) => error!(target: "kona_host", "Session #{session_id} failed: {e:#}"),
+    }
+
+    // Close the connection, so that the client does not wait on a session that has ended.
+    if let Err(e) = transport.shutdown() {
+        error!(target: "kona_host", "{e}");
+    }
+}
+
+/// Runs a [PreimageServer] over the given oracle server and hint reader until the client
+/// disconnects.
+async fn serve_preimages<P, H, KV>(
+    oracle_server: P,
+    hint_reader: H,
+    kv_store: Arc<KV>,
+    fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+    witness: Option<Arc<RwLock<Witness>>>,
+) -> Result<()>
+where
+    P: PreimageOracleServer + Send + Sync + 'static,
+    H: HintReaderServer + Send + Sync + 'static,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
+{
+    let mut server = PreimageServer::new(oracle_server, hint_reader, kv_store, fetcher);
+    if let Some(witness) = witness {
+        server = server.with_witness(witness);
+    }
     AssertUnwindSafe(server.start())
         .catch_unwind()
         .await
@@ -160,7 +341,7 @@ where
         })?
         .map_err(|e| {
             error!(target: "preimage_server", "Preimage server exited with an error");
-            anyhow!("Preimage server exited with an error: {:?}", e)
+            e.context("Preimage server exited with an error")
         })?;
 
     Ok(())
@@ -176,8 +357,9 @@ where
 /// - `rx`: The receiver to wait for the preimage server to exit.
 ///
 /// ## Returns
-/// - `Ok(())` if the client program exits successfully.
-/// - `Err(_)` if the client program exits with a non-zero status.
+/// - `Ok(())` if the client program validated the claim.
+/// - `Err(_)` if the client program found the claim to be invalid, in which case the error is an
+///   [InvalidClaim], or if it failed to run to completion.
 pub async fn start_native_client_program(
     cfg: HostCli,
     hint_pipe: Pipe,
@@ -220,19 +402,52 @@ pub async fn start_native_client_program(
         ])
         .expect("No errors may occur when mapping file descriptors.");
 
-    let status = command
-        .status()
-        .await
-        .map_err(|e| {
-            error!(target: "client_program", "Failed to execute client program: {:?}", e);
-            anyhow!("Failed to execute client program: {:?}", e)
-        })?
-        .success();
+    let status = command.status().await.map_err(|e| {
+        error!(target: "client_program", "Failed to execute client program: {:?}", e);
+        anyhow!("Failed to execute client program: {:?}", e)
+    })?;
+
+    match status.code().map(ExitCode::try_from) {
+        Some(Ok(ExitCode::Valid)) => {
+            info!(target: "client_program", "Client program validated the claim.");
+            Ok(())
+        }
+        Some(Ok(ExitCode::Invalid)) => {
+            error!(target: "client_program", "Client program found the claim to be invalid.");
+            Err(anyhow::Error::msg(InvalidClaim))
+        }
+        Some(Ok(ExitCode::Error)) | Some(Err(_)) => {
+            error!(target: "client_program", "Client program failed with {status}.");
+            Err(anyhow!("Client program failed with {status}."))
+        }
+        None => {
+            error!(target: "client_program", "Client program was terminated by a signal.");
+            Err(anyhow!("Client program was terminated by a signal."))
+        }
+    }
+}
 
-    if !status {
-        error!(target: "client_program", "Client program exited with a non-zero status.");
-        return Err(anyhow!("Client program exited with a non-zero status."));
+/// Writes the recorded [Witness] to the given path, if witness export is enabled, and returns the
+/// result of the session that it was recorded in. The witness is written even if the session
+/// failed, so that the failing run can be reproduced offline.
+async fn export_witness(
+    path: Option<&Path>,
+    witness: Option<Arc<RwLock<Witness>>>,
+    session: Result<()>,
+) -> Result<()> {
+    if let (Some(path), Some(witness)) = (path, witness) {
+        let witness = witness.read().await;
+        match witness.write_to_file(path) {
+            Ok(()) => {
+                info!(target: "kona_host", "Exported witness with {} preimages to {}", witness.len(), path.display())
+            }
+            // Report the session's failure rather than the export's, which is secondary to it.
+            Err(e) if session.is_err() => {
+                error!(target: "kona_host", "Failed to export witness: {e}")
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    Ok(())
+    session
 }
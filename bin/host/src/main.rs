@@ -1,19 +1,85 @@
 use anyhow::Result;
-use clap::Parser;
-use kona_host::{init_tracing_subscriber, start_server, start_server_and_native_client, HostCli};
-use tracing::info;
+use kona_common::ExitCode;
+use kona_host::{
+    diagnostics::{InvalidClaim, MissingPreimage, MISSING_PREIMAGE_EXIT_CODE},
+    init_tracing_subscriber, metrics, start_server, start_server_and_native_client,
+    start_server_and_native_clients, start_server_daemon, HostCli,
+};
+use tracing::{error, info};
 
 #[tokio::main(flavor = "multi_thread")]
-async fn main() -> Result<()> {
-    let cfg = HostCli::parse();
-    init_tracing_subscriber(cfg.v)?;
+async fn main() {
+    // Failures to set up the host are reported with the generic error exit code. Returning them
+    // from `main` would exit with status 1, which is the exit code of an invalid claim.
+    let cfg = match init().await {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            std::process::exit(ExitCode::Error.into());
+        }
+    };
 
-    if cfg.server {
-        start_server(cfg).await?;
+    let report_path = cfg.missing_preimage_report.clone();
+    let result = if cfg.server {
+        start_server(cfg).await
+    } else if cfg.listen.is_some() {
+        start_server_daemon(cfg).await
+    } else if cfg.sessions.is_some() {
+        start_server_and_native_clients(cfg).await
     } else {
-        start_server_and_native_client(cfg).await?;
+        start_server_and_native_client(cfg).await
+    };
+
+    // Report the outcome of the session through the exit code. A missing preimage means that the
+    // host's preimages are incomplete, rather than that the claim is invalid, so it is reported
+    // with a distinct exit code.
+    if let Err(e) = result {
+        let code = if let Some(missing) = e.downcast_ref::<MissingPreimage>() {
+            if let Some(path) = report_path {
+                match missing.write_report(&path) {
+                    Ok(()) => info!(
+                        target: "kona_host",
+                        "Wrote missing preimage report to {}",
+                        path.display()
+                    ),
+                    Err(report_err) => error!(
+                        target: "kona_host",
+                        "Failed to write missing preimage report to {}: {report_err:#}",
+                        path.display()
+                    ),
+                }
+            }
+            MISSING_PREIMAGE_EXIT_CODE
+        } else if e.downcast_ref::<InvalidClaim>().is_some() {
+            ExitCode::Invalid.into()
+        } else {
+            ExitCode::Error.into()
+        };
+
+        error!(target: "kona_host", "{e:#}");
+        std::process::exit(code);
     }
 
     info!("Exiting host program.");
-    Ok(())
+}
+
+/// Parses and validates the host's configuration, initializes tracing and starts the metrics
+/// server, if enabled.
+async fn init() -> Result<HostCli> {
+    let mut cfg = HostCli::parse_with_config()?;
+    init_tracing_subscriber(cfg.v)?;
+    cfg.validate()?;
+    cfg.resolve_inputs().await?;
+
+    if let Some(addr) = cfg.metrics_addr {
+        info!("Serving metrics on {addr}.");
+        let server = metrics::serve_metrics(addr)?;
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                error!(target: "metrics", "Metrics server failed: {e}");
+            }
+        });
+    }
+
+    Ok(cfg)
 }
@@ -0,0 +1,121 @@
+//! Metrics for the host program, and the server that exposes them to Prometheus.
+
+use actix_web::{dev::Server, get, App, HttpServer, Responder};
+use anyhow::{anyhow, Result};
+use kona_preimage::PreimageKeyType;
+use lazy_static::lazy_static;
+use prometheus::{
+    self, register_histogram_vec, register_int_counter, register_int_counter_vec, Encoder,
+    HistogramVec, IntCounter, IntCounterVec, TextEncoder,
+};
+use std::net::SocketAddr;
+
+const FETCH_LATENCY_BUCKETS: &[f64; 14] =
+    &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+lazy_static! {
+    /// Tracks the preimage requests served to the client program, by preimage key type.
+    pub static ref PREIMAGE_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "kona_host_preimage_requests",
+        "Number of preimage requests served, by preimage key type",
+        &["key_type"]
+    ).expect("Preimage requests failed to register");
+
+    /// Tracks the preimage requests that could not be served, by preimage key type.
+    pub static ref PREIMAGE_REQUEST_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "kona_host_preimage_request_errors",
+        "Number of preimage requests that could not be served, by preimage key type",
+        &["key_type"]
+    ).expect("Preimage request errors failed to register");
+
+    /// Tracks the total number of preimage bytes served to the client program.
+    pub static ref PREIMAGE_BYTES_SERVED: IntCounter = register_int_counter!(
+        "kona_host_preimage_bytes_served",
+        "Total number of preimage bytes served"
+    ).expect("Preimage bytes served failed to register");
+
+    /// Tracks key-value store lookups for requested preimages, by whether the preimage was
+    /// already present.
+    pub static ref KV_CACHE_LOOKUPS: IntCounterVec = register_int_counter_vec!(
+        "kona_host_kv_cache_lookups",
+        "Number of key-value store lookups for requested preimages, by result",
+        &["result"]
+    ).expect("KV cache lookups failed to register");
+
+    /// Tracks the latency of fetching the preimages for a hint from the remote source, by hint
+    /// type.
+    pub static ref HINT_FETCH_LATENCY: HistogramVec = register_histogram_vec!(
+        "kona_host_hint_fetch_latency_seconds",
+        "Latency of fetching the preimages for a hint, by hint type",
+        &["hint_type"],
+        FETCH_LATENCY_BUCKETS.to_vec()
+    ).expect("Hint fetch latency failed to register");
+
+    /// Tracks the hints whose preimages could not be fetched, by hint type.
+    pub static ref HINT_FETCH_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "kona_host_hint_fetch_errors",
+        "Number of failed hint fetches, by hint type",
+        &["hint_type"]
+    ).expect("Hint fetch errors failed to register");
+
+    /// Tracks the number of times the pending hints were re-fetched in the foreground, because a
+    /// requested preimage was still missing.
+    pub static ref FETCH_RETRIES: IntCounter = register_int_counter!(
+        "kona_host_fetch_retries",
+        "Number of foreground retries of the pending hints"
+    ).expect("Fetch retries failed to register");
+
+    /// Tracks the failed requests to remote endpoints, by endpoint.
+    pub static ref RPC_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "kona_host_rpc_errors",
+        "Number of failed requests to remote endpoints, by endpoint",
+        &["endpoint"]
+    ).expect("RPC errors failed to register");
+}
+
+/// Returns the label of a [PreimageKeyType] within the metrics.
+pub fn key_type_label(key_type: PreimageKeyType) -> &'static str {
+    match key_type {
+        PreimageKeyType::Local => "local",
+        PreimageKeyType::Keccak256 => "keccak256",
+        PreimageKeyType::GlobalGeneric => "global_generic",
+        PreimageKeyType::Sha256 => "sha256",
+        PreimageKeyType::Blob => "blob",
+        PreimageKeyType::Precompile => "precompile",
+    }
+}
+
+/// Binds the metrics server to the given address. The returned [Server] serves the metrics until
+/// it is dropped or the process exits.
+pub fn serve_metrics(addr: SocketAddr) -> Result<Server> {
+    // Register all metrics up front, so that they are exposed before they are first recorded.
+    lazy_static::initialize(&PREIMAGE_REQUESTS);
+    lazy_static::initialize(&PREIMAGE_REQUEST_ERRORS);
+    lazy_static::initialize(&PREIMAGE_BYTES_SERVED);
+    lazy_static::initialize(&KV_CACHE_LOOKUPS);
+    lazy_static::initialize(&HINT_FETCH_LATENCY);
+    lazy_static::initialize(&HINT_FETCH_ERRORS);
+    lazy_static::initialize(&FETCH_RETRIES);
+    lazy_static::initialize(&RPC_ERRORS);
+
+    Ok(HttpServer::new(|| App::new().service(index).service(metrics))
+        .bind(addr)
+        .map_err(|e| anyhow!("Failed to bind metrics server to {addr}: {e}"))?
+        .run())
+}
+
+#[get("/")]
+async fn index() -> impl Responder {
+    "kona-host-metrics-server: visit /metrics to view metrics"
+}
+
+#[get("/metrics")]
+async fn metrics() -> impl Responder {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        tracing::error!(target: "metrics", "Failed to encode prometheus metrics: {e}");
+    }
+
+    String::from_utf8(buffer).expect("Prometheus text encoding is valid UTF-8")
+}
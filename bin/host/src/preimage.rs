@@ -1,17 +1,21 @@
 //! Contains the implementations of the [HintRouter] and [PreimageFetcher] traits.]
 
-use crate::{fetcher::Fetcher, kv::KeyValueStore};
+use crate::{
+    diagnostics::OfflineDiagnostics, fetcher::Fetcher, kv::AsyncKeyValueStore, metrics,
+    witness::Witness,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use kona_preimage::{HintRouter, PreimageFetcher, PreimageKey};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::error;
 
 /// A [Fetcher]-backed implementation of the [PreimageFetcher] trait.
 #[derive(Debug)]
 pub struct OnlinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     inner: Arc<RwLock<Fetcher<KV>>>,
 }
@@ -19,17 +23,19 @@ where
 #[async_trait]
 impl<KV> PreimageFetcher for OnlinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + Send + Sync + ?Sized,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized,
 {
     async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
         let fetcher = self.inner.read().await;
-        fetcher.get_preimage(key.into()).await
+        fetcher.get_preimage(key.into()).await.inspect_err(|e| {
+            error!(target: "preimage_server", "Failed to fetch preimage for key {key}: {e}");
+        })
     }
 }
 
 impl<KV> OnlinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     /// Create a new [OnlinePreimageFetcher] from the given [Fetcher].
     pub fn new(fetcher: Arc<RwLock<Fetcher<KV>>>) -> Self {
@@ -37,33 +43,122 @@ where
     }
 }
 
-/// A [KeyValueStore]-backed implementation of the [PreimageFetcher] trait.
+/// An [AsyncKeyValueStore]-backed implementation of the [PreimageFetcher] trait. Preimages that
+/// are not in the store are recorded in the [OfflineDiagnostics].
 #[derive(Debug)]
 pub struct OfflinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
-    inner: Arc<RwLock<KV>>,
+    inner: Arc<KV>,
+    diagnostics: Arc<OfflineDiagnostics>,
 }
 
 #[async_trait]
 impl<KV> PreimageFetcher for OfflinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + Send + Sync + ?Sized,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized,
 {
     async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
-        let kv_store = self.inner.read().await;
-        kv_store.get(key.into()).ok_or_else(|| anyhow::anyhow!("Key not found"))
+        let preimage = self.inner.get(key.into()).await.inspect_err(|e| {
+            error!(target: "preimage_server", "Failed to fetch preimage for key {key}: {e}");
+        })?;
+
+        let result = if preimage.is_some() { "hit" } else { "miss" };
+        metrics::KV_CACHE_LOOKUPS.with_label_values(&[result]).inc();
+
+        match preimage {
+            Some(preimage) => Ok(preimage),
+            None => {
+                let missing = self.diagnostics.record_missing(key).await;
+                error!(target: "preimage_server", "{missing}");
+                Err(anyhow::Error::msg(missing))
+            }
+        }
     }
 }
 
 impl<KV> OfflinePreimageFetcher<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
-    /// Create a new [OfflinePreimageFetcher] from the given [KeyValueStore].
-    pub fn new(kv_store: Arc<RwLock<KV>>) -> Self {
-        Self { inner: kv_store }
+    /// Create a new [OfflinePreimageFetcher] from the given [AsyncKeyValueStore], recording
+    /// missing preimages in the given [OfflineDiagnostics].
+    pub fn new(kv_store: Arc<KV>, diagnostics: Arc<OfflineDiagnostics>) -> Self {
+        Self { inner: kv_store, diagnostics }
+    }
+}
+
+/// A [PreimageFetcher] that records every preimage served by an inner [PreimageFetcher] into a
+/// [Witness].
+#[derive(Debug)]
+pub struct RecordingPreimageFetcher<F>
+where
+    F: PreimageFetcher,
+{
+    inner: F,
+    witness: Arc<RwLock<Witness>>,
+}
+
+#[async_trait]
+impl<F> PreimageFetcher for RecordingPreimageFetcher<F>
+where
+    F: PreimageFetcher + Send + Sync,
+{
+    async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        let preimage = self.inner.get_preimage(key).await?;
+        self.witness.set(key.into(), preimage.clone()).await?;
+        Ok(preimage)
+    }
+}
+
+impl<F> RecordingPreimageFetcher<F>
+where
+    F: PreimageFetcher,
+{
+    /// Create a new [RecordingPreimageFetcher] that records the preimages served by `inner` into
+    /// the given [Witness].
+    pub fn new(inner: F, witness: Arc<RwLock<Witness>>) -> Self {
+        Self { inner, witness }
+    }
+}
+
+/// A [PreimageFetcher] that records the requests served by an inner [PreimageFetcher] into the
+/// host's [metrics].
+#[derive(Debug)]
+pub struct MeteredPreimageFetcher<F>
+where
+    F: PreimageFetcher,
+{
+    inner: F,
+}
+
+#[async_trait]
+impl<F> PreimageFetcher for MeteredPreimageFetcher<F>
+where
+    F: PreimageFetcher + Send + Sync,
+{
+    async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        let key_type = metrics::key_type_label(key.key_type());
+        let result = self.inner.get_preimage(key).await;
+        match result {
+            Ok(ref preimage) => {
+                metrics::PREIMAGE_REQUESTS.with_label_values(&[key_type]).inc();
+                metrics::PREIMAGE_BYTES_SERVED.inc_by(preimage.len() as u64);
+            }
+            Err(_) => metrics::PREIMAGE_REQUEST_ERRORS.with_label_values(&[key_type]).inc(),
+        }
+        result
+    }
+}
+
+impl<F> MeteredPreimageFetcher<F>
+where
+    F: PreimageFetcher,
+{
+    /// Create a new [MeteredPreimageFetcher] that records the requests served by `inner`.
+    pub fn new(inner: F) -> Self {
+        Self { inner }
     }
 }
 
@@ -71,7 +166,7 @@ where
 #[derive(Debug)]
 pub struct OnlineHintRouter<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     inner: Arc<RwLock<Fetcher<KV>>>,
 }
@@ -79,18 +174,18 @@ where
 #[async_trait]
 impl<KV> HintRouter for OnlineHintRouter<KV>
 where
-    KV: KeyValueStore + Send + Sync + ?Sized,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
 {
     async fn route_hint(&self, hint: String) -> Result<()> {
         let mut fetcher = self.inner.write().await;
-        fetcher.hint(&hint);
+        fetcher.hint(&hint).await;
         Ok(())
     }
 }
 
 impl<KV> OnlineHintRouter<KV>
 where
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     /// Create a new [OnlineHintRouter] from the given [Fetcher].
     pub fn new(fetcher: Arc<RwLock<Fetcher<KV>>>) -> Self {
@@ -98,13 +193,24 @@ where
     }
 }
 
-/// An [OfflineHintRouter] is a [HintRouter] that does nothing.
+/// An [OfflineHintRouter] is a [HintRouter] that fetches nothing, and only records the hints it
+/// receives in the [OfflineDiagnostics].
 #[derive(Debug)]
-pub struct OfflineHintRouter;
+pub struct OfflineHintRouter {
+    diagnostics: Arc<OfflineDiagnostics>,
+}
 
 #[async_trait]
 impl HintRouter for OfflineHintRouter {
-    async fn route_hint(&self, _hint: String) -> Result<()> {
+    async fn route_hint(&self, hint: String) -> Result<()> {
+        self.diagnostics.record_hint(hint).await;
         Ok(())
     }
 }
+
+impl OfflineHintRouter {
+    /// Create a new [OfflineHintRouter] that records hints in the given [OfflineDiagnostics].
+    pub fn new(diagnostics: Arc<OfflineDiagnostics>) -> Self {
+        Self { diagnostics }
+    }
+}
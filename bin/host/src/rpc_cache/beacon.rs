@@ -0,0 +1,114 @@
+//! Contains the [RpcCacheBeaconClient], a [BeaconClient] that records and replays Beacon API
+//! responses through an [RpcCache].
+
+use super::{RpcCache, RpcCacheMode};
+use crate::metrics;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use kona_derive::online::BeaconClient;
+use kona_primitives::{
+    APIBlobSidecar, APIConfigResponse, APIGenesisResponse, APIGetBlobSidecarsResponse,
+    IndexedBlobHash,
+};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// The config spec Beacon API method.
+const SPEC_METHOD: &str = "eth/v1/config/spec";
+
+/// The beacon genesis Beacon API method.
+const GENESIS_METHOD: &str = "eth/v1/beacon/genesis";
+
+/// The blob sidecars Beacon API method prefix.
+const SIDECARS_METHOD_PREFIX: &str = "eth/v1/beacon/blob_sidecars";
+
+/// The name of the Beacon API endpoint within the cache.
+const BEACON_ENDPOINT: &str = "beacon";
+
+/// A [BeaconClient] that records the raw Beacon API responses it receives into an [RpcCache], or
+/// serves them from it, depending on the [RpcCacheMode]. Without a cache, it behaves like the
+/// [OnlineBeaconClient].
+///
+/// [OnlineBeaconClient]: kona_derive::online::OnlineBeaconClient
+#[derive(Debug, Clone)]
+pub struct RpcCacheBeaconClient {
+    /// The base URL of the Beacon API.
+    base: String,
+    /// The inner reqwest client.
+    inner: Client,
+    /// The cache that responses are recorded in or replayed from.
+    cache: Option<Arc<RpcCache>>,
+}
+
+impl RpcCacheBeaconClient {
+    /// Create a new [RpcCacheBeaconClient] for the Beacon API at the given base URL.
+    pub fn new(base: String, cache: Option<Arc<RpcCache>>) -> Self {
+        Self { base, inner: Client::new(), cache }
+    }
+
+    /// Fetches the response to the given Beacon API method, recording or replaying the raw
+    /// response body, and deserializes it.
+    async fn get<T: DeserializeOwned>(&self, method: String) -> Result<T> {
+        let key = serde_json::to_string(&method)
+            .map_err(|e| anyhow!("Failed to serialize Beacon API method: {e}"))?;
+
+        let body = match self.cache {
+            Some(ref cache) if cache.mode() == RpcCacheMode::Replay => cache
+                .get(BEACON_ENDPOINT, &key)
+                .await?
+                .ok_or_else(|| anyhow!("No recorded Beacon API response for {method}"))?,
+            _ => {
+                let response = self
+                    .inner
+                    .get(format!("{}/{}", self.base, method))
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| {
+                        metrics::RPC_ERRORS.with_label_values(&[BEACON_ENDPOINT]).inc();
+                        anyhow!(e)
+                    })?;
+                let body = response.text().await.map_err(|e| {
+                    metrics::RPC_ERRORS.with_label_values(&[BEACON_ENDPOINT]).inc();
+                    anyhow!(e)
+                })?;
+                if let Some(ref cache) = self.cache {
+                    cache.set(BEACON_ENDPOINT, &key, &body).await?;
+                }
+                body
+            }
+        };
+
+        serde_json::from_str(&body).map_err(|e| anyhow!("Failed to decode {method} response: {e}"))
+    }
+}
+
+#[async_trait]
+impl BeaconClient for RpcCacheBeaconClient {
+    async fn config_spec(&self) -> Result<APIConfigResponse> {
+        self.get(SPEC_METHOD.to_string()).await
+    }
+
+    async fn beacon_genesis(&self) -> Result<APIGenesisResponse> {
+        self.get(GENESIS_METHOD.to_string()).await
+    }
+
+    async fn beacon_blob_side_cars(
+        &self,
+        slot: u64,
+        hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<APIBlobSidecar>> {
+        let response: APIGetBlobSidecarsResponse =
+            self.get(format!("{SIDECARS_METHOD_PREFIX}/{slot}")).await?;
+
+        // Filter the sidecars by the hashes, in-order.
+        Ok(hashes
+            .iter()
+            .filter_map(|hash| {
+                response.data.iter().find(|sidecar| sidecar.inner.index == hash.index as u64)
+            })
+            .cloned()
+            .collect())
+    }
+}
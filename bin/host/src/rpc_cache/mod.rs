@@ -0,0 +1,134 @@
+//! This module contains the [RpcCache], which records the raw responses of the JSON-RPC and Beacon
+//! API endpoints that the host fetches from, and replays them without network access.
+//!
+//! Recorded responses are stored as one JSON file per request, in a directory per endpoint:
+//! ```text
+//! <cache dir>/<endpoint>/<keccak256(request)>.json = { "request": .., "response": .. }
+//! ```
+//! The files hold the exact response that was received, so a recorded cache directory can be
+//! shared as a fixture to reproduce a run bit-for-bit.
+
+use alloy_primitives::keccak256;
+use alloy_provider::RootProvider;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use kona_derive::online::{OnlineBlobProvider, SimpleSlotDerivation};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::{fs, path::PathBuf};
+use tokio::task;
+
+mod transport;
+pub use transport::RpcCacheTransport;
+
+mod beacon;
+pub use beacon::RpcCacheBeaconClient;
+
+/// A JSON-RPC provider whose responses are recorded or replayed by an [RpcCache].
+pub type RpcProvider = RootProvider<RpcCacheTransport>;
+
+/// A blob provider whose Beacon API responses are recorded or replayed by an [RpcCache].
+pub type BeaconBlobProvider = OnlineBlobProvider<RpcCacheBeaconClient, SimpleSlotDerivation>;
+
+/// The modes that an [RpcCache] can operate in.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RpcCacheMode {
+    /// Send every request to its endpoint, and record each successful response.
+    #[default]
+    Record,
+    /// Serve every request from the recorded responses, without network access. Requests that
+    /// were not recorded fail.
+    Replay,
+}
+
+/// A recorded request and its response.
+#[derive(Serialize, Deserialize)]
+struct RpcCacheEntry {
+    /// The request, as JSON.
+    request: Box<RawValue>,
+    /// The raw response to the request.
+    response: Box<RawValue>,
+}
+
+/// A directory of recorded endpoint responses.
+#[derive(Debug)]
+pub struct RpcCache {
+    /// The directory that responses are recorded in.
+    directory: PathBuf,
+    /// The mode that the cache operates in.
+    mode: RpcCacheMode,
+}
+
+impl RpcCache {
+    /// Create a new [RpcCache] over the given directory. In [RpcCacheMode::Record], the directory
+    /// is created if it does not already exist. In [RpcCacheMode::Replay], it must exist.
+    pub fn new(directory: PathBuf, mode: RpcCacheMode) -> Result<Self> {
+        match mode {
+            RpcCacheMode::Record => fs::create_dir_all(&directory)
+                .map_err(|e| anyhow!("Failed to create RPC cache directory: {e}"))?,
+            RpcCacheMode::Replay if !directory.is_dir() => {
+                anyhow::bail!("RPC cache directory {} does not exist", directory.display())
+            }
+            RpcCacheMode::Replay => {}
+        }
+
+        Ok(Self { directory, mode })
+    }
+
+    /// Returns the mode that the cache operates in.
+    pub const fn mode(&self) -> RpcCacheMode {
+        self.mode
+    }
+
+    /// Returns the recorded response to the given request to an endpoint, if there is one.
+    pub async fn get(&self, endpoint: &str, request: &str) -> Result<Option<String>> {
+        let path = self.entry_path(endpoint, request);
+        task::spawn_blocking(move || {
+            if !path.exists() {
+                return Ok(None);
+            }
+
+            let entry = fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read RPC cache entry {}: {e}", path.display()))?;
+            let entry: RpcCacheEntry = serde_json::from_str(&entry)
+                .map_err(|e| anyhow!("Invalid RPC cache entry {}: {e}", path.display()))?;
+            Ok(Some(entry.response.get().to_string()))
+        })
+        .await
+        .map_err(|e| anyhow!("RPC cache task failed: {e}"))?
+    }
+
+    /// Records the response to the given request to an endpoint. Both the request and the response
+    /// must be valid JSON.
+    pub async fn set(&self, endpoint: &str, request: &str, response: &str) -> Result<()> {
+        let path = self.entry_path(endpoint, request);
+        let entry = RpcCacheEntry {
+            request: RawValue::from_string(request.to_string())
+                .map_err(|e| anyhow!("Request is not valid JSON: {e}"))?,
+            response: RawValue::from_string(response.to_string())
+                .map_err(|e| anyhow!("Response is not valid JSON: {e}"))?,
+        };
+
+        task::spawn_blocking(move || {
+            let parent = path.parent().expect("Entries are always within a directory");
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow!("Failed to create RPC cache directory: {e}"))?;
+
+            // Write to a temporary file first, so that concurrent readers never observe a partially
+            // written entry.
+            let serialized = serde_json::to_vec(&entry)
+                .map_err(|e| anyhow!("Failed to serialize RPC cache entry: {e}"))?;
+            let tmp_path = path.with_extension("json.tmp");
+            fs::write(&tmp_path, serialized)
+                .and_then(|_| fs::rename(&tmp_path, &path))
+                .map_err(|e| anyhow!("Failed to write RPC cache entry {}: {e}", path.display()))
+        })
+        .await
+        .map_err(|e| anyhow!("RPC cache task failed: {e}"))?
+    }
+
+    /// Returns the path of the file that the response to the given request is recorded in.
+    fn entry_path(&self, endpoint: &str, request: &str) -> PathBuf {
+        self.directory.join(endpoint).join(format!("{}.json", keccak256(request.as_bytes())))
+    }
+}
@@ -0,0 +1,150 @@
+//! Contains the [RpcCacheTransport], a JSON-RPC transport that records and replays responses
+//! through an [RpcCache].
+
+use super::{RpcCache, RpcCacheMode};
+use crate::metrics;
+use alloy_json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest};
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use alloy_transport_http::Http;
+use reqwest::Client;
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::Service;
+
+/// An HTTP JSON-RPC transport that records the responses it receives into an [RpcCache], or
+/// serves them from it, depending on the [RpcCacheMode]. Without a cache, requests are passed
+/// straight through to the endpoint.
+#[derive(Debug, Clone)]
+pub struct RpcCacheTransport {
+    /// The HTTP transport that requests are sent over.
+    inner: Http<Client>,
+    /// The cache that responses are recorded in or replayed from.
+    cache: Option<Arc<RpcCache>>,
+    /// The name of the endpoint within the cache.
+    endpoint: &'static str,
+}
+
+impl RpcCacheTransport {
+    /// Create a new [RpcCacheTransport] for the endpoint at the given URL. Responses are recorded
+    /// under the given endpoint name within the cache.
+    pub fn new(url: reqwest::Url, cache: Option<Arc<RpcCache>>, endpoint: &'static str) -> Self {
+        Self { inner: Http::new(url), cache, endpoint }
+    }
+}
+
+impl Service<RequestPacket> for RpcCacheTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let endpoint = self.endpoint;
+        let Some(cache) = self.cache.clone() else {
+            let response = self.inner.call(req);
+            return Box::pin(async move { record_errors(endpoint, response.await) });
+        };
+
+        match cache.mode() {
+            RpcCacheMode::Record => {
+                let keys = requests(&req)
+                    .iter()
+                    .map(|req| (req.id().clone(), cache_key(req)))
+                    .collect::<Vec<_>>();
+                let response = self.inner.call(req);
+
+                Box::pin(async move {
+                    let response = record_errors(endpoint, response.await)?;
+                    let responses = match &response {
+                        ResponsePacket::Single(response) => core::slice::from_ref(response),
+                        ResponsePacket::Batch(responses) => responses.as_slice(),
+                    };
+
+                    // Only successful responses are recorded, so that transient errors are never
+                    // replayed.
+                    for response in responses {
+                        let ResponsePayload::Success(ref payload) = response.payload else {
+                            continue;
+                        };
+                        let Some((_, key)) = keys.iter().find(|(id, _)| *id == response.id) else {
+                            continue;
+                        };
+                        cache.set(endpoint, key, payload.get()).await.map_err(|e| {
+                            TransportErrorKind::custom_str(&format!("Failed to record: {e}"))
+                        })?;
+                    }
+
+                    Ok(response)
+                })
+            }
+            RpcCacheMode::Replay => Box::pin(async move {
+                let mut responses = Vec::new();
+                for req in requests(&req) {
+                    let key = cache_key(req);
+                    let payload = cache
+                        .get(endpoint, &key)
+                        .await
+                        .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?
+                        .ok_or_else(|| {
+                            TransportErrorKind::custom_str(&format!(
+                                "No recorded {endpoint} response for {key}"
+                            ))
+                        })?;
+                    let payload = serde_json::value::RawValue::from_string(payload)
+                        .map_err(TransportErrorKind::custom)?;
+                    responses.push(Response {
+                        id: req.id().clone(),
+                        payload: ResponsePayload::Success(payload),
+                    });
+                }
+
+                Ok(match req {
+                    RequestPacket::Single(_) => {
+                        ResponsePacket::Single(responses.pop().expect("One request was replayed"))
+                    }
+                    RequestPacket::Batch(_) => ResponsePacket::Batch(responses),
+                })
+            }),
+        }
+    }
+}
+
+/// Records the transport errors and error responses of a request to an endpoint into the host's
+/// [metrics], and passes the result through.
+fn record_errors(
+    endpoint: &'static str,
+    result: Result<ResponsePacket, TransportError>,
+) -> Result<ResponsePacket, TransportError> {
+    let errors = match result {
+        Ok(ResponsePacket::Single(ref response)) => response.payload.is_error() as u64,
+        Ok(ResponsePacket::Batch(ref responses)) => {
+            responses.iter().filter(|response| response.payload.is_error()).count() as u64
+        }
+        Err(_) => 1,
+    };
+    metrics::RPC_ERRORS.with_label_values(&[endpoint]).inc_by(errors);
+    result
+}
+
+/// Returns the requests within a [RequestPacket].
+fn requests(packet: &RequestPacket) -> &[SerializedRequest] {
+    match packet {
+        RequestPacket::Single(req) => core::slice::from_ref(req),
+        RequestPacket::Batch(reqs) => reqs.as_slice(),
+    }
+}
+
+/// Returns the key that the response to a request is recorded under. The key is made up of the
+/// method and parameters of the request, and excludes its ID.
+fn cache_key(req: &SerializedRequest) -> String {
+    format!(
+        r#"{{"method":"{}","params":{}}}"#,
+        req.method(),
+        req.params().map_or("null", |p| p.get())
+    )
+}
@@ -1,16 +1,19 @@
 //! This module contains the [PreimageServer] struct and its implementation.
 
 use crate::{
+    diagnostics::OfflineDiagnostics,
     fetcher::Fetcher,
-    kv::KeyValueStore,
+    kv::AsyncKeyValueStore,
     preimage::{
-        OfflineHintRouter, OfflinePreimageFetcher, OnlineHintRouter, OnlinePreimageFetcher,
+        MeteredPreimageFetcher, OfflineHintRouter, OfflinePreimageFetcher, OnlineHintRouter,
+        OnlinePreimageFetcher, RecordingPreimageFetcher,
     },
+    witness::Witness,
 };
 use anyhow::{anyhow, Result};
 use kona_preimage::{HintReaderServer, HintRouter, PreimageFetcher, PreimageOracleServer};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::{runtime::Handle, sync::RwLock, task};
 
 /// The [PreimageServer] is responsible for waiting for incoming preimage requests and
 /// serving them to the client.
@@ -19,89 +22,141 @@ pub struct PreimageServer<P, H, KV>
 where
     P: PreimageOracleServer,
     H: HintReaderServer,
-    KV: KeyValueStore + ?Sized,
+    KV: AsyncKeyValueStore + ?Sized,
 {
     /// The oracle server.
     oracle_server: P,
     /// The hint router.
     hint_reader: H,
     /// Key-value store for preimages.
-    kv_store: Arc<RwLock<KV>>,
+    kv_store: Arc<KV>,
     /// The fetcher for fetching preimages from a remote source. If [None], the server will only
     /// serve preimages that are already in the key-value store.
     fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+    /// The [Witness] that all served preimages are recorded into. If [None], served preimages are
+    /// not recorded.
+    witness: Option<Arc<RwLock<Witness>>>,
 }
 
 impl<P, H, KV> PreimageServer<P, H, KV>
 where
     P: PreimageOracleServer + Send + Sync + 'static,
     H: HintReaderServer + Send + Sync + 'static,
-    KV: KeyValueStore + Send + Sync + ?Sized + 'static,
+    KV: AsyncKeyValueStore + Send + Sync + ?Sized + 'static,
 {
     /// Create a new [PreimageServer] with the given [PreimageOracleServer],
-    /// [HintReaderServer], and [KeyValueStore]. Holds onto the file descriptors for the pipes
+    /// [HintReaderServer], and [AsyncKeyValueStore]. Holds onto the file descriptors for the pipes
     /// that are created, so that the pipes are not closed until the server is dropped.
     pub fn new(
         oracle_server: P,
         hint_reader: H,
-        kv_store: Arc<RwLock<KV>>,
+        kv_store: Arc<KV>,
         fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
     ) -> Self {
-        Self { oracle_server, hint_reader, kv_store, fetcher }
+        Self { oracle_server, hint_reader, kv_store, fetcher, witness: None }
+    }
+
+    /// Records every preimage served by the [PreimageServer] into the given [Witness].
+    pub fn with_witness(mut self, witness: Arc<RwLock<Witness>>) -> Self {
+        self.witness = Some(witness);
+        self
     }
 
     /// Starts the [PreimageServer] and waits for incoming requests.
+    ///
+    /// ## Returns
+    /// - `Ok(())` once the client disconnects.
+    /// - `Err(_)` if the server is offline and a requested preimage was missing. The error is a
+    ///   [MissingPreimage](crate::diagnostics::MissingPreimage).
     pub async fn start(self) -> Result<()> {
-        // Create the futures for the oracle server and hint router.
+        // Create the futures for the oracle server and hint router. Both record into the same
+        // diagnostics when offline.
+        let diagnostics = Arc::new(OfflineDiagnostics::default());
         let server_fut = Self::start_oracle_server(
             self.kv_store.clone(),
             self.fetcher.clone(),
+            self.witness,
+            diagnostics.clone(),
             self.oracle_server,
         );
-        let hinter_fut = Self::start_hint_router(self.hint_reader, self.fetcher);
+        let hinter_fut =
+            Self::start_hint_router(self.hint_reader, self.fetcher, diagnostics.clone());
 
-        // Spawn tasks for the futures and wait for them to complete.
-        let server = tokio::task::spawn(server_fut);
-        let hint_router = tokio::task::spawn(hinter_fut);
+        // Spawn tasks for the futures and wait for them to complete. Both perform blocking reads on
+        // their pipes while waiting for the client, so each is driven on a dedicated blocking
+        // thread rather than occupying one of the runtime's workers. This keeps the workers free
+        // to fetch preimages, regardless of how many servers are running within the host.
+        let handle = Handle::current();
+        let server = task::spawn_blocking({
+            let handle = handle.clone();
+            move || handle.block_on(server_fut)
+        });
+        let hint_router = task::spawn_blocking(move || handle.block_on(hinter_fut));
         tokio::select! {
             s = server => s.map_err(|e| anyhow!(e))?,
             h = hint_router => h.map_err(|e| anyhow!(e))?,
         }
 
-        Ok(())
+        match diagnostics.missing().await {
+            Some(missing) => Err(anyhow::Error::msg(missing)),
+            None => Ok(()),
+        }
     }
 
     /// Starts the oracle server, which waits for incoming preimage requests and serves them to the
     /// client.
     async fn start_oracle_server(
-        kv_store: Arc<RwLock<KV>>,
+        kv_store: Arc<KV>,
         fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+        witness: Option<Arc<RwLock<Witness>>>,
+        diagnostics: Arc<OfflineDiagnostics>,
         oracle_server: P,
     ) {
         #[inline(always)]
-        async fn do_loop<F, P>(fetcher: &F, server: &P)
+        async fn do_loop<F, P>(fetcher: F, server: &P)
         where
             F: PreimageFetcher + Send + Sync,
             P: PreimageOracleServer,
         {
+            let fetcher = MeteredPreimageFetcher::new(fetcher);
             loop {
-                // Break the loop on any error. An error in this path indicates a closed pipe.
-                if server.next_preimage_request(fetcher).await.is_err() {
+                // Break the loop on any error. An error in this path indicates either a closed
+                // pipe, or a preimage that could not be fetched or stored. In the
+                // latter case, no response is written for the request, and the
+                // pipes are closed once the server exits, causing the client to
+                // fail cleanly rather than receive bad data.
+                if server.next_preimage_request(&fetcher).await.is_err() {
                     break;
                 }
             }
         }
 
-        if let Some(fetcher) = fetcher.as_ref() {
-            do_loop(&OnlinePreimageFetcher::new(Arc::clone(fetcher)), &oracle_server).await;
-        } else {
-            do_loop(&OfflinePreimageFetcher::new(Arc::clone(&kv_store)), &oracle_server).await;
-        };
+        match (fetcher.as_ref(), witness) {
+            (Some(fetcher), Some(witness)) => {
+                let fetcher = OnlinePreimageFetcher::new(Arc::clone(fetcher));
+                do_loop(RecordingPreimageFetcher::new(fetcher, witness), &oracle_server).await;
+            }
+            (Some(fetcher), None) => {
+                do_loop(OnlinePreimageFetcher::new(Arc::clone(fetcher)), &oracle_server).await;
+            }
+            (None, Some(witness)) => {
+                let fetcher = OfflinePreimageFetcher::new(Arc::clone(&kv_store), diagnostics);
+                do_loop(RecordingPreimageFetcher::new(fetcher, witness), &oracle_server).await;
+            }
+            (None, None) => {
+                let fetcher = OfflinePreimageFetcher::new(Arc::clone(&kv_store), diagnostics);
+                do_loop(fetcher, &oracle_server).await;
+            }
+        }
     }
 
     /// Starts the hint router, which waits for incoming hints and routes them to the appropriate
     /// handler.
-    async fn start_hint_router(hint_reader: H, fetcher: Option<Arc<RwLock<Fetcher<KV>>>>) {
+    async fn start_hint_router(
+        hint_reader: H,
+        fetcher: Option<Arc<RwLock<Fetcher<KV>>>>,
+        diagnostics: Arc<OfflineDiagnostics>,
+    ) {
         #[inline(always)]
         async fn do_loop<R, H>(router: &R, server: &H)
         where
@@ -119,7 +174,7 @@ where
         if let Some(fetcher) = fetcher {
             do_loop(&OnlineHintRouter::new(Arc::clone(&fetcher)), &hint_reader).await;
         } else {
-            do_loop(&OfflineHintRouter, &hint_reader).await;
+            do_loop(&OfflineHintRouter::new(diagnostics), &hint_reader).await;
         }
     }
 }
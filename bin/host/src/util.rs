@@ -1,13 +1,19 @@
 //! Contains utility functions and helpers for the host program.
 
-use alloy_primitives::{hex, Bytes};
-use alloy_provider::ReqwestProvider;
-use alloy_rpc_client::RpcClient;
-use alloy_transport_http::Http;
+use crate::rpc_cache::{RpcCache, RpcCacheTransport, RpcProvider};
+use alloy_consensus::Header;
+use alloy_eips::BlockId;
+use alloy_primitives::{hex, Bytes, B256};
+use alloy_provider::Provider;
+use alloy_rlp::Decodable;
+use alloy_rpc_client::{BatchRequest, RpcClient};
 use anyhow::{anyhow, Result};
-use kona_client::HintType;
+use kona_primitives::{
+    Hint, OutputRoot, OutputRootV0, HINT_VERSION_0, L2_TO_L1_MESSAGE_PASSER_ADDRESS,
+};
 use os_pipe::{PipeReader, PipeWriter};
-use reqwest::Client;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, sync::Arc};
 use tokio::task::JoinHandle;
 
 /// A bidirectional pipe, with a client and host end.
@@ -37,27 +43,84 @@ pub fn bidirectional_pipe() -> Result<BidirectionalPipe> {
 
 /// Parses a hint from a string.
 ///
-/// Hints are of the format `<hint_type> <hint_data>`, where `<hint_type>` is a string that
-/// represents the type of hint, and `<hint_data>` is the data associated with the hint
-/// (bytes encoded as hex UTF-8).
-pub(crate) fn parse_hint(s: &str) -> Result<(HintType, Bytes)> {
-    let mut parts = s.split(' ').collect::<Vec<_>>();
-
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid hint format: {}", s);
+/// Hints are of the format `<hint_type>[/v<version>] <hint_data>`, where `<hint_type>` is a string
+/// that represents the type of hint, `<version>` is the version of the hint data encoding, and
+/// `<hint_data>` is the data associated with the hint (bytes encoded as hex UTF-8). The version
+/// suffix is stripped from the returned hint type, and only [HINT_VERSION_0] is supported.
+pub(crate) fn parse_hint(s: &str) -> Result<(&str, Bytes)> {
+    let (hint_type, version, hint_data) = Hint::split(s).map_err(|e| anyhow!("{e}"))?;
+    if version != HINT_VERSION_0 {
+        anyhow::bail!("Unsupported version {version} of {hint_type} hint");
     }
-
-    let hint_type = HintType::try_from(parts.remove(0))?;
-    let hint_data = hex::decode(parts.remove(0)).map_err(|e| anyhow!(e))?.into();
+    let hint_data = hex::decode(hint_data).map_err(|e| anyhow!(e))?.into();
 
     Ok((hint_type, hint_data))
 }
 
-/// Returns an HTTP provider for the given URL.
-pub(crate) fn http_provider(url: &str) -> ReqwestProvider {
+/// Returns an HTTP provider for the given URL. If an [RpcCache] is given, the provider's responses
+/// are recorded in or replayed from it, under the given endpoint name.
+pub(crate) fn http_provider(
+    url: &str,
+    rpc_cache: Option<Arc<RpcCache>>,
+    endpoint: &'static str,
+) -> RpcProvider {
     let url = url.parse().unwrap();
-    let http = Http::<Client>::new(url);
-    ReqwestProvider::new(RpcClient::new(http, true))
+    let transport = RpcCacheTransport::new(url, rpc_cache, endpoint);
+    RpcProvider::new(RpcClient::new(transport, true))
+}
+
+/// Sends a single JSON-RPC batch request to the given provider, calling `method` once for each
+/// entry in `params`. The responses are returned in the same order as the `params`.
+pub(crate) async fn batch_request<P, R>(
+    provider: &RpcProvider,
+    method: &'static str,
+    params: &[P],
+) -> Result<Vec<R>>
+where
+    P: Serialize + Clone + Debug + Send + Sync + Unpin + 'static,
+    R: DeserializeOwned + Debug + Send + Sync + Unpin + 'static,
+{
+    if params.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut batch = BatchRequest::new(provider.client());
+    let waiters = params
+        .iter()
+        .map(|p| batch.add_call::<P, R>(method, p))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow!("Failed to serialize batch request: {e}"))?;
+    batch.send().await.map_err(|e| anyhow!("Failed to send batch request: {e}"))?;
+
+    futures::future::try_join_all(waiters)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch batch response: {e}"))
+}
+
+/// Fetches the [OutputRoot] of the L2 block with the given hash, computed from the block's header
+/// and the storage root of the `L2ToL1MessagePasser` predeploy.
+pub(crate) async fn fetch_output_root(
+    l2_provider: &RpcProvider,
+    block_hash: B256,
+) -> Result<OutputRoot> {
+    // Fetch the header for the L2 block.
+    let raw_header: Bytes = l2_provider
+        .client()
+        .request("debug_getRawHeader", &[block_hash])
+        .await
+        .map_err(|e| anyhow!("Failed to fetch header RLP: {e}"))?;
+    let header = Header::decode(&mut raw_header.as_ref())
+        .map_err(|e| anyhow!("Failed to decode header: {e}"))?;
+
+    // Fetch the storage root of the message passer for the L2 block.
+    let l2_to_l1_message_passer = l2_provider
+        .get_proof(L2_TO_L1_MESSAGE_PASSER_ADDRESS, Default::default())
+        .block_id(BlockId::Hash(block_hash.into()))
+        .await
+        .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+
+    Ok(OutputRootV0::new(header.state_root, l2_to_l1_message_passer.storage_hash, block_hash)
+        .into())
 }
 
 /// Flattens the result of a [JoinHandle] into a single result.
@@ -65,11 +128,11 @@ pub(crate) async fn flatten_join_result<T, E>(
     handle: JoinHandle<Result<T, E>>,
 ) -> Result<T, anyhow::Error>
 where
-    E: std::fmt::Display,
+    E: Into<anyhow::Error>,
 {
     match handle.await {
         Ok(Ok(result)) => Ok(result),
-        Ok(Err(err)) => Err(anyhow!("{}", err)),
+        Ok(Err(err)) => Err(err.into()),
         Err(err) => anyhow::bail!(err),
     }
 }
@@ -0,0 +1,120 @@
+//! This module contains the [Witness] type, a portable bundle of all preimages that were requested
+//! by the client program during a run.
+//!
+//! **Layout** (all integers are big-endian):
+//! ```text
+//! witness = magic ++ version ++ entry_count ++ entry*
+//! magic   = "KWIT" (4 bytes)
+//! version = u8
+//! entry   = key (32 bytes) ++ length (u64) ++ preimage (length bytes)
+//! ```
+//! Entries are sorted by key, so that the same set of preimages always produces the same file.
+
+use crate::kv::KeyValueStore;
+use alloy_primitives::B256;
+use anyhow::{anyhow, ensure, Result};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The magic bytes at the start of every witness file.
+const WITNESS_MAGIC: [u8; 4] = *b"KWIT";
+
+/// The current version of the witness encoding.
+const WITNESS_VERSION: u8 = 1;
+
+/// The size of the witness header, in bytes.
+const WITNESS_HEADER_SIZE: usize = WITNESS_MAGIC.len() + 1 + 8;
+
+/// A portable bundle of preimages, keyed by their [PreimageKey]s. A [Witness] is itself a
+/// [KeyValueStore], so that the host may serve preimages purely from it.
+///
+/// [PreimageKey]: kona_preimage::PreimageKey
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+pub struct Witness {
+    preimages: BTreeMap<B256, Vec<u8>>,
+}
+
+impl Witness {
+    /// Create a new, empty [Witness].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of preimages in the witness.
+    pub fn len(&self) -> usize {
+        self.preimages.len()
+    }
+
+    /// Returns `true` if the witness contains no preimages.
+    pub fn is_empty(&self) -> bool {
+        self.preimages.is_empty()
+    }
+
+    /// Encodes the witness into its versioned binary format.
+    pub fn encode(&self) -> Vec<u8> {
+        let body_size = self.preimages.values().map(|v| 32 + 8 + v.len()).sum::<usize>();
+        let mut buf = Vec::with_capacity(WITNESS_HEADER_SIZE + body_size);
+
+        buf.extend_from_slice(&WITNESS_MAGIC);
+        buf.push(WITNESS_VERSION);
+        buf.extend_from_slice(&(self.preimages.len() as u64).to_be_bytes());
+        for (key, value) in self.preimages.iter() {
+            buf.extend_from_slice(key.as_slice());
+            buf.extend_from_slice(&(value.len() as u64).to_be_bytes());
+            buf.extend_from_slice(value);
+        }
+
+        buf
+    }
+
+    /// Decodes a witness from its versioned binary format.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        ensure!(data.len() >= WITNESS_HEADER_SIZE, "Witness is too short");
+        ensure!(data[..4] == WITNESS_MAGIC, "Invalid witness magic bytes");
+        ensure!(
+            data[4] == WITNESS_VERSION,
+            "Unsupported witness version: {} (expected {})",
+            data[4],
+            WITNESS_VERSION
+        );
+
+        let count = u64::from_be_bytes(data[5..13].try_into().map_err(|e| anyhow!("{e}"))?);
+        let mut preimages = BTreeMap::new();
+        let mut cursor = &data[WITNESS_HEADER_SIZE..];
+        for i in 0..count {
+            ensure!(cursor.len() >= 40, "Witness entry #{i} is truncated");
+            let key = B256::from_slice(&cursor[..32]);
+            let len =
+                u64::from_be_bytes(cursor[32..40].try_into().map_err(|e| anyhow!("{e}"))?) as usize;
+            cursor = &cursor[40..];
+
+            ensure!(cursor.len() >= len, "Witness entry #{i} is truncated");
+            preimages.insert(key, cursor[..len].to_vec());
+            cursor = &cursor[len..];
+        }
+        ensure!(cursor.is_empty(), "Witness has {} trailing bytes", cursor.len());
+
+        Ok(Self { preimages })
+    }
+
+    /// Reads and decodes a witness from the file at the given path.
+    pub fn read_from_file(path: &Path) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| anyhow!("Failed to read witness file: {e}"))?;
+        Self::decode(&data)
+    }
+
+    /// Encodes and writes the witness to the file at the given path.
+    pub fn write_to_file(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.encode()).map_err(|e| anyhow!("Failed to write witness file: {e}"))
+    }
+}
+
+impl KeyValueStore for Witness {
+    fn get(&self, key: B256) -> Result<Option<Vec<u8>>> {
+        Ok(self.preimages.get(&key).cloned())
+    }
+
+    fn set(&mut self, key: B256, value: Vec<u8>) -> Result<()> {
+        self.preimages.insert(key, value);
+        Ok(())
+    }
+}
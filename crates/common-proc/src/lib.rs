@@ -20,6 +20,9 @@ impl Parse for MacroArgs {
     }
 }
 
+/// Marks the entry point of a client program. The body of the function must evaluate to a
+/// `Result` whose value converts into a [kona_common::ExitCode], such as `()` for a program that
+/// validated its claim. Errors and panics exit with [kona_common::ExitCode::Error].
 #[proc_macro_attribute]
 pub fn client_entry(attr: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as MacroArgs);
@@ -34,10 +37,10 @@ pub fn client_entry(attr: TokenStream, input: TokenStream) -> TokenStream {
 
         fn #fn_name() -> AnyhowResult<()> {
             match #fn_body {
-                Ok(_) => kona_common::io::exit(0),
+                Ok(code) => kona_common::io::exit(kona_common::ExitCode::from(code).into()),
                 Err(e) => {
                     kona_common::io::print_err(alloc::format!("Program encountered fatal error: {:?}\n", e).as_ref());
-                    kona_common::io::exit(1);
+                    kona_common::io::exit(kona_common::ExitCode::Error.into());
                 }
             }
         }
@@ -57,7 +60,7 @@ pub fn client_entry(attr: TokenStream, input: TokenStream) -> TokenStream {
                 fn panic(info: &core::panic::PanicInfo) -> ! {
                     let msg = alloc::format!("Panic: {}", info);
                     kona_common::io::print_err(msg.as_ref());
-                    kona_common::io::exit(2)
+                    kona_common::io::exit(kona_common::ExitCode::Error.into())
                 }
             }
         }
@@ -15,7 +15,7 @@ mod traits;
 pub use traits::BasicKernelInterface;
 
 mod types;
-pub use types::FileDescriptor;
+pub use types::{ExitCode, FileDescriptor};
 
 mod executor;
 pub use executor::block_on;
@@ -41,3 +41,52 @@ impl From<FileDescriptor> for i32 {
         usize::from(fd) as i32
     }
 }
+
+/// The exit codes of a client program. The codes follow the convention of the fault proof
+/// program, so that a claim that was found to be invalid is distinguishable from a program that
+/// failed to run to completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The program ran to completion, and the claim is valid.
+    Valid,
+    /// The program ran to completion, and the claim is invalid.
+    Invalid,
+    /// The program failed to run to completion, due to an error or a panic.
+    Error,
+}
+
+impl From<ExitCode> for usize {
+    fn from(code: ExitCode) -> Self {
+        match code {
+            ExitCode::Valid => 0,
+            ExitCode::Invalid => 1,
+            ExitCode::Error => 2,
+        }
+    }
+}
+
+impl From<ExitCode> for i32 {
+    fn from(code: ExitCode) -> Self {
+        usize::from(code) as i32
+    }
+}
+
+impl TryFrom<i32> for ExitCode {
+    type Error = anyhow::Error;
+
+    fn try_from(code: i32) -> anyhow::Result<Self> {
+        Ok(match code {
+            0 => ExitCode::Valid,
+            1 => ExitCode::Invalid,
+            2 => ExitCode::Error,
+            _ => anyhow::bail!("Invalid exit code: {code}"),
+        })
+    }
+}
+
+/// A program that ran to completion without reporting an [ExitCode] has validated its claim.
+impl From<()> for ExitCode {
+    fn from(_: ()) -> Self {
+        ExitCode::Valid
+    }
+}
@@ -10,10 +10,13 @@ extern crate alloc;
 use alloc::vec::Vec;
 use alloy_consensus::{Header, Sealable, EMPTY_OMMER_ROOT_HASH, EMPTY_ROOT_HASH};
 use alloy_eips::eip2718::{Decodable2718, Encodable2718};
-use alloy_primitives::{address, keccak256, Address, Bytes, TxKind, B256, U256};
+use alloy_primitives::{address, keccak256, Bytes, TxKind, B256, U256};
 use anyhow::{anyhow, Result};
 use kona_mpt::{ordered_trie_with_encoder, TrieDB, TrieDBFetcher, TrieDBHinter};
-use kona_primitives::{L2PayloadAttributes, RawTransaction, RollupConfig};
+use kona_primitives::{
+    L2PayloadAttributes, OutputRootV0, RawTransaction, RollupConfig,
+    L2_TO_L1_MESSAGE_PASSER_ADDRESS,
+};
 use op_alloy_consensus::{OpReceiptEnvelope, OpTxEnvelope};
 use revm::{
     db::{states::bundle_state::BundleRetention, State},
@@ -327,10 +330,6 @@ where
     /// - `Ok(output_root)`: The computed output root.
     /// - `Err(_)`: If an error occurred while computing the output root.
     pub fn compute_output_root(&mut self) -> Result<B256> {
-        const OUTPUT_ROOT_VERSION: u8 = 0;
-        const L2_TO_L1_MESSAGE_PASSER_ADDRESS: Address =
-            address!("4200000000000000000000000000000000000016");
-
         // Fetch the L2 to L1 message passer account from the cache or underlying trie.
         let storage_root = match self.trie_db.storage_roots().get(&L2_TO_L1_MESSAGE_PASSER_ADDRESS)
         {
@@ -350,18 +349,14 @@ where
         info!(
             target: "client_executor",
             "Computing output root | Version: {version} | State root: {state_root} | Storage root: {storage_root} | Block hash: {hash}",
-            version = OUTPUT_ROOT_VERSION,
+            version = OutputRootV0::VERSION,
             state_root = self.trie_db.parent_block_header().state_root,
             hash = parent_header.seal(),
         );
 
-        // Construct the raw output.
-        let mut raw_output = [0u8; 128];
-        raw_output[31] = OUTPUT_ROOT_VERSION;
-        raw_output[32..64].copy_from_slice(parent_header.state_root.as_ref());
-        raw_output[64..96].copy_from_slice(storage_root.as_ref());
-        raw_output[96..128].copy_from_slice(parent_header.seal().as_ref());
-        let output_root = keccak256(raw_output);
+        // Construct the output and hash it.
+        let output_root =
+            OutputRootV0::new(parent_header.state_root, storage_root, parent_header.seal()).hash();
 
         info!(
             target: "client_executor",
@@ -0,0 +1,87 @@
+//! This module contains the wire format of the batched preimage request extension, which allows the
+//! client to request several preimages from the host in a single exchange.
+//!
+//! **Negotiation**: The client sends the [BATCH_REQUEST_HINT] over the hint channel. Hosts that
+//! support the extension acknowledge it with `0x01` rather than routing it. Hosts and FPVMs that do
+//! not support it acknowledge it like any other hint, and the client falls back to requesting one
+//! preimage at a time.
+//!
+//! **Request** (all integers are big-endian):
+//! ```text
+//! request = header ++ key_0 ++ .. ++ key_{n - 1}
+//! header  = 0xFF ++ [0x00; 27] ++ n (u32, 1 <= n <= MAX_BATCH_SIZE)
+//! ```
+//! The header takes the place of a preimage key, and its leading byte is not a valid
+//! [PreimageKeyType], so it can never be mistaken for a single request.
+//!
+//! **Response**: For each key in the request, in order, the length of its preimage as a `u64`
+//! followed by the preimage, as in a single request.
+//!
+//! [PreimageKeyType]: crate::PreimageKeyType
+
+use anyhow::{bail, Result};
+
+/// The hint that the client sends to negotiate batched preimage requests with the host.
+pub const BATCH_REQUEST_HINT: &str = "preimage-batch 01";
+
+/// The acknowledgement with which the host accepts batched preimage requests.
+pub(crate) const BATCH_REQUEST_ACK: u8 = 0x01;
+
+/// The maximum number of keys within a single batched preimage request.
+pub const MAX_BATCH_SIZE: usize = 1024;
+
+/// The leading byte of a batched preimage request header.
+const BATCH_REQUEST_TYPE: u8 = 0xFF;
+
+/// Encodes the header of a batched preimage request for the given number of keys.
+pub(crate) fn encode_batch_header(count: usize) -> [u8; 32] {
+    let mut header = [0u8; 32];
+    header[0] = BATCH_REQUEST_TYPE;
+    header[28..].copy_from_slice(&(count as u32).to_be_bytes());
+    header
+}
+
+/// Decodes the number of keys from the header of a batched preimage request.
+///
+/// # Returns
+/// - `Ok(Some(count))` if the buffer is a valid batched preimage request header.
+/// - `Ok(None)` if the buffer is not a batched preimage request header.
+/// - `Err(_)` if the buffer is a malformed batched preimage request header.
+pub(crate) fn decode_batch_header(header: &[u8; 32]) -> Result<Option<usize>> {
+    if header[0] != BATCH_REQUEST_TYPE {
+        return Ok(None);
+    }
+    if header[1..28].iter().any(|b| *b != 0) {
+        bail!("Invalid batched preimage request header");
+    }
+
+    let count = u32::from_be_bytes([header[28], header[29], header[30], header[31]]) as usize;
+    if count == 0 || count > MAX_BATCH_SIZE {
+        bail!("Invalid batched preimage request size {count}, expected 1 to {MAX_BATCH_SIZE}");
+    }
+    Ok(Some(count))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_batch_header() {
+        for count in [1, 2, MAX_BATCH_SIZE] {
+            assert_eq!(decode_batch_header(&encode_batch_header(count)).unwrap(), Some(count));
+        }
+
+        // Preimage keys are not batch headers.
+        let mut key = [0xFFu8; 32];
+        key[0] = crate::PreimageKeyType::Keccak256 as u8;
+        assert_eq!(decode_batch_header(&key).unwrap(), None);
+
+        // Empty, oversized and malformed batches are rejected.
+        assert!(decode_batch_header(&encode_batch_header(0)).is_err());
+        assert!(decode_batch_header(&encode_batch_header(MAX_BATCH_SIZE + 1)).is_err());
+        let mut header = encode_batch_header(1);
+        header[1] = 0x01;
+        assert!(decode_batch_header(&header).is_err());
+    }
+}
@@ -0,0 +1,636 @@
+//! Conformance tests for the hint and preimage protocols, which replay the golden transcripts in
+//! `testdata/conformance` against the [HintWriter] and [OracleReader] clients and the [HintReader]
+//! and [OracleServer] hosts. The transcript format is described in the README of that directory.
+
+extern crate std;
+
+use crate::{
+    batch::decode_batch_header, test_utils::bidirectional_pipe, Channel, HintReader,
+    HintReaderServer, HintRouter, HintWriter, HintWriterClient, OracleReader, OracleServer,
+    PipeHandle, PreimageFetcher, PreimageKey, PreimageOracleClient, PreimageOracleServer,
+    BATCH_REQUEST_HINT,
+};
+use alloc::{
+    boxed::Box,
+    collections::VecDeque,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use alloy_primitives::hex;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use kona_common::FileDescriptor;
+use os_pipe::{PipeReader, PipeWriter};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    os::fd::AsRawFd,
+    path::Path,
+    sync::Mutex,
+    thread::{self, JoinHandle},
+};
+
+/// The channel that a transcript message is exchanged over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    Hint,
+    Preimage,
+}
+
+/// The direction that a transcript message is sent in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// From the client to the host, written as `>`.
+    ToHost,
+    /// From the host to the client, written as `<`.
+    ToClient,
+}
+
+/// A single message within a [Transcript].
+#[derive(Debug)]
+struct Message {
+    stream: Stream,
+    direction: Direction,
+    bytes: Vec<u8>,
+}
+
+/// A golden transcript of the bytes exchanged between a client and a host.
+#[derive(Debug)]
+struct Transcript {
+    /// The file name of the transcript.
+    name: String,
+    /// The messages of the transcript, in order.
+    messages: Vec<Message>,
+    /// The direction whose stream is closed at the end of the transcript, if any.
+    closed: Option<Direction>,
+}
+
+impl Transcript {
+    /// Parses a [Transcript] from its text representation.
+    fn parse(name: &str, text: &str) -> Result<Self> {
+        let mut transcript = Self { name: name.to_string(), messages: Vec::new(), closed: None };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if transcript.closed.is_some() {
+                bail!("{name}:{}: `eof` must be the last line", i + 1);
+            }
+
+            let (header, body) = line.split_once(' ').unwrap_or((line, ""));
+            let (stream, direction) = match header {
+                "eof>" | "eof<" if !body.is_empty() => {
+                    bail!("{name}:{}: unexpected bytes after `{header}`", i + 1)
+                }
+                "eof>" => {
+                    transcript.closed = Some(Direction::ToHost);
+                    continue;
+                }
+                "eof<" => {
+                    transcript.closed = Some(Direction::ToClient);
+                    continue;
+                }
+                "hint>" => (Stream::Hint, Direction::ToHost),
+                "hint<" => (Stream::Hint, Direction::ToClient),
+                "preimage>" => (Stream::Preimage, Direction::ToHost),
+                "preimage<" => (Stream::Preimage, Direction::ToClient),
+                _ => bail!("{name}:{}: unknown message `{header}`", i + 1),
+            };
+
+            let mut bytes = Vec::new();
+            for token in body.split_whitespace() {
+                let (hex, count) = match token.split_once('*') {
+                    Some((hex, count)) => {
+                        (hex, count.parse::<usize>().map_err(|e| anyhow!("{name}:{}: {e}", i + 1))?)
+                    }
+                    None => (token, 1),
+                };
+                let decoded =
+                    hex::decode(hex).map_err(|e| anyhow!("{name}:{}: {e}", i + 1))?.repeat(count);
+                bytes.extend(decoded);
+            }
+            transcript.messages.push(Message { stream, direction, bytes });
+        }
+
+        Ok(transcript)
+    }
+
+    /// Loads all transcripts in `testdata/conformance`, in order of their file names.
+    fn load_all() -> Vec<Self> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/conformance");
+        let mut paths = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "transcript"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert!(!paths.is_empty(), "No conformance transcripts found");
+
+        paths
+            .iter()
+            .map(|path| {
+                let name = path.file_name().unwrap().to_string_lossy();
+                Self::parse(&name, &std::fs::read_to_string(path).unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    /// Returns every request in the transcript, paired with its response if there is one.
+    fn exchanges(&self) -> Vec<(&Message, Option<&Message>)> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.direction == Direction::ToHost)
+            .map(|(i, request)| {
+                let response = self.messages[i + 1..]
+                    .iter()
+                    .filter(|message| message.stream == request.stream)
+                    .take_while(|message| message.direction == Direction::ToClient)
+                    .next();
+                (request, response)
+            })
+            .collect()
+    }
+
+    /// Returns the concatenated bytes that are sent over the given stream in the given direction.
+    fn bytes(&self, stream: Stream, direction: Direction) -> Vec<u8> {
+        self.messages
+            .iter()
+            .filter(|message| message.stream == stream && message.direction == direction)
+            .flat_map(|message| message.bytes.iter().copied())
+            .collect()
+    }
+
+    /// Returns whether the request at the given index must fail, because the transcript is closed
+    /// during its exchange.
+    fn must_fail(&self, index: usize) -> bool {
+        self.closed.is_some() && index + 1 == self.exchanges().len()
+    }
+
+    /// Returns whether the transcript checks clients. Transcripts in which the client closes the
+    /// connection only check hosts.
+    fn checks_client(&self) -> bool {
+        self.closed != Some(Direction::ToHost)
+    }
+
+    /// Returns whether the transcript checks hosts. Transcripts in which the host closes the
+    /// connection only check clients.
+    fn checks_host(&self) -> bool {
+        self.closed != Some(Direction::ToClient)
+    }
+}
+
+/// Decodes a `hint>` message into the hint that it carries.
+fn decode_hint(bytes: &[u8]) -> Result<String> {
+    let (length, hint) =
+        bytes.split_first_chunk::<4>().ok_or_else(|| anyhow!("Hint is missing its length"))?;
+    if u32::from_be_bytes(*length) as usize != hint.len() {
+        bail!("Hint length does not match its payload");
+    }
+    String::from_utf8(hint.to_vec()).map_err(|e| anyhow!("Hint is not valid UTF-8: {e}"))
+}
+
+/// Decodes a `preimage>` message into the [PreimageKey]s that it requests, which may be a single
+/// key or a batch.
+fn decode_keys(bytes: &[u8]) -> Result<Vec<PreimageKey>> {
+    let (first, rest) =
+        bytes.split_first_chunk::<32>().ok_or_else(|| anyhow!("Request is shorter than a key"))?;
+    let count = match decode_batch_header(first)? {
+        Some(count) => count,
+        None if rest.is_empty() => return Ok(vec![PreimageKey::try_from(*first)?]),
+        None => bail!("Request is longer than a key"),
+    };
+
+    if rest.len() != count * 32 {
+        bail!("Batched request does not contain {count} keys");
+    }
+    rest.chunks_exact(32)
+        .map(|key| PreimageKey::try_from(<[u8; 32]>::try_from(key).expect("Keys are 32 bytes")))
+        .collect()
+}
+
+/// Decodes a `preimage<` message into the length-prefixed preimages that it carries.
+fn decode_preimages(mut bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut preimages = Vec::new();
+    while !bytes.is_empty() {
+        let (length, rest) = bytes
+            .split_first_chunk::<8>()
+            .ok_or_else(|| anyhow!("Preimage is missing its length"))?;
+        let length = u64::from_be_bytes(*length) as usize;
+        if rest.len() < length {
+            bail!("Preimage is shorter than its length");
+        }
+        preimages.push(rest[..length].to_vec());
+        bytes = &rest[length..];
+    }
+    Ok(preimages)
+}
+
+/// Returns whether a hint exchange negotiates batched preimage requests, and if so, whether the
+/// host accepted them.
+fn batch_negotiation(request: &Message, response: Option<&Message>) -> Option<bool> {
+    let hint = decode_hint(&request.bytes).ok()?;
+    (hint == BATCH_REQUEST_HINT).then(|| response.is_some_and(|r| r.bytes == [0x01]))
+}
+
+/// The ways in which a client may read a preimage.
+#[derive(Debug, Clone, Copy)]
+enum ReadMethod {
+    /// [PreimageOracleClient::get].
+    Get,
+    /// [PreimageOracleClient::get_chunked], with the given chunk size.
+    Chunked(usize),
+}
+
+impl ReadMethod {
+    /// Reads the preimage for the given key with the [ReadMethod].
+    async fn read<C: Channel + Send + Sync>(
+        self,
+        reader: &OracleReader<C>,
+        key: PreimageKey,
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::Get => reader.get(key).await,
+            Self::Chunked(chunk_size) => {
+                let mut buf = vec![0u8; chunk_size];
+                let mut preimage = Vec::new();
+                reader
+                    .get_chunked(key, &mut buf, |chunk| {
+                        preimage.extend_from_slice(chunk);
+                        Ok(())
+                    })
+                    .await?;
+                Ok(preimage)
+            }
+        }
+    }
+}
+
+/// Replays the requests of a [Transcript] with the [HintWriter] and [OracleReader] clients, and
+/// checks the preimages that they return.
+async fn replay_client<C: Channel + Send + Sync>(
+    transcript: &Transcript,
+    hint_channel: C,
+    preimage_channel: C,
+    read_method: ReadMethod,
+) {
+    let name = &transcript.name;
+    let hint_writer = HintWriter::new(hint_channel);
+    let mut oracle_reader = OracleReader::new(preimage_channel);
+
+    for (i, (request, response)) in transcript.exchanges().into_iter().enumerate() {
+        let result = match request.stream {
+            Stream::Hint => match batch_negotiation(request, response) {
+                Some(expected) => {
+                    let result = hint_writer.negotiate_batch_requests().await;
+                    if let Ok(accepted) = result {
+                        assert_eq!(accepted, expected, "{name}: client misread the negotiation");
+                        oracle_reader = oracle_reader.with_batch_requests(accepted);
+                    }
+                    result.map(|_| None)
+                }
+                None => {
+                    let hint = decode_hint(&request.bytes).unwrap();
+                    hint_writer.write(&hint).await.map(|_| None)
+                }
+            },
+            Stream::Preimage => {
+                // Batched requests are longer than a single key, as they hold at least one key
+                // after their header.
+                let keys = decode_keys(&request.bytes).unwrap();
+                if request.bytes.len() > 32 {
+                    oracle_reader.get_batch(&keys).await.map(Some)
+                } else {
+                    read_method.read(&oracle_reader, keys[0]).await.map(|p| Some(vec![p]))
+                }
+            }
+        };
+
+        if transcript.must_fail(i) {
+            assert!(result.is_err(), "{name}: client accepted a truncated exchange");
+            continue;
+        }
+        let preimages = result.unwrap_or_else(|e| panic!("{name}: client failed: {e}"));
+        if let Some(preimages) = preimages {
+            let response = response.expect("Requests are followed by a response");
+            let expected = decode_preimages(&response.bytes).unwrap();
+            assert_eq!(preimages, expected, "{name}: client returned the wrong preimages");
+        }
+    }
+}
+
+/// A [HintRouter] that records the hints that it routes.
+#[derive(Debug, Default)]
+struct RecordingRouter {
+    hints: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl HintRouter for RecordingRouter {
+    async fn route_hint(&self, hint: String) -> Result<()> {
+        self.hints.lock().unwrap().push(hint);
+        Ok(())
+    }
+}
+
+/// A [PreimageFetcher] that serves the preimages of a [Transcript].
+#[derive(Debug)]
+struct TranscriptFetcher {
+    preimages: HashMap<PreimageKey, Vec<u8>>,
+}
+
+#[async_trait]
+impl PreimageFetcher for TranscriptFetcher {
+    async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        self.preimages.get(&key).cloned().ok_or_else(|| anyhow!("Preimage not found for key {key}"))
+    }
+}
+
+/// Replays the requests of a [Transcript] against the [HintReader] and [OracleServer] hosts, and
+/// checks the hints that they route.
+async fn replay_host<C: Channel + Send + Sync>(
+    transcript: &Transcript,
+    hint_channel: C,
+    preimage_channel: C,
+) {
+    let name = &transcript.name;
+    let exchanges = transcript.exchanges();
+
+    // The host accepts batched requests if it does so in the transcript.
+    let batch_requests = exchanges
+        .iter()
+        .any(|&(request, response)| batch_negotiation(request, response) == Some(true));
+    let hint_reader = HintReader::new(hint_channel).with_batch_requests(batch_requests);
+    let oracle_server = OracleServer::new(preimage_channel);
+
+    let router = RecordingRouter::default();
+    let fetcher = TranscriptFetcher {
+        preimages: exchanges
+            .iter()
+            .filter(|(request, _)| request.stream == Stream::Preimage)
+            .filter_map(|&(request, response)| {
+                let keys = decode_keys(&request.bytes).ok()?;
+                let preimages = decode_preimages(&response?.bytes).ok()?;
+                Some(keys.into_iter().zip(preimages))
+            })
+            .flatten()
+            .collect(),
+    };
+
+    let mut expected_hints = Vec::new();
+    for (i, (request, _)) in exchanges.into_iter().enumerate() {
+        let result = match request.stream {
+            Stream::Hint => hint_reader.next_hint(&router).await,
+            Stream::Preimage => oracle_server.next_preimage_request(&fetcher).await,
+        };
+
+        if transcript.must_fail(i) {
+            assert!(result.is_err(), "{name}: host accepted a truncated exchange");
+            continue;
+        }
+        result.unwrap_or_else(|e| panic!("{name}: host failed: {e}"));
+        if request.stream == Stream::Hint {
+            let hint = decode_hint(&request.bytes).unwrap();
+            if !(batch_requests && hint == BATCH_REQUEST_HINT) {
+                expected_hints.push(hint);
+            }
+        }
+    }
+
+    assert_eq!(
+        *router.hints.lock().unwrap(),
+        expected_hints,
+        "{name}: host routed the wrong hints"
+    );
+}
+
+/// An in-memory [Channel], which serves a fixed sequence of bytes to its reader and records the
+/// bytes written to it. Reads past the end of the sequence fail, as if the peer had closed the
+/// channel.
+#[derive(Debug, Clone)]
+struct MemoryChannel {
+    incoming: Arc<Mutex<VecDeque<u8>>>,
+    outgoing: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemoryChannel {
+    fn new(incoming: Vec<u8>) -> Self {
+        Self { incoming: Arc::new(Mutex::new(incoming.into())), outgoing: Default::default() }
+    }
+
+    fn written(&self) -> Vec<u8> {
+        self.outgoing.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Channel for MemoryChannel {
+    async fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut incoming = self.incoming.lock().unwrap();
+        let n = buf.len().min(incoming.len());
+        for (dst, src) in buf.iter_mut().zip(incoming.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+
+    async fn read_exact(&self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.read(buf).await?;
+        if n < buf.len() {
+            bail!("Channel closed after reading {n} of {} bytes", buf.len());
+        }
+        Ok(n)
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize> {
+        self.outgoing.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+/// A pipe to a peer thread, which writes a fixed sequence of bytes to the implementation under test
+/// in small fragments and then closes its end, while collecting the bytes that the implementation
+/// writes.
+#[derive(Debug)]
+struct FragmentedPeer {
+    read: PipeReader,
+    write: PipeWriter,
+    feeder: JoinHandle<()>,
+    collector: JoinHandle<Vec<u8>>,
+}
+
+impl FragmentedPeer {
+    fn spawn(bytes: Vec<u8>) -> Self {
+        let pipe = bidirectional_pipe().unwrap();
+        let (mut peer_read, mut peer_write) = (pipe.client.read, pipe.client.write);
+
+        let feeder = thread::spawn(move || {
+            let mut offset = 0;
+            for size in (1..=7).cycle() {
+                if offset == bytes.len() {
+                    break;
+                }
+                let end = bytes.len().min(offset + size);
+                // The implementation may stop reading early if it fails the exchange.
+                if peer_write.write_all(&bytes[offset..end]).is_err() {
+                    break;
+                }
+                offset = end;
+            }
+        });
+        let collector = thread::spawn(move || {
+            let mut written = Vec::new();
+            peer_read.read_to_end(&mut written).unwrap();
+            written
+        });
+
+        Self { read: pipe.host.read, write: pipe.host.write, feeder, collector }
+    }
+
+    /// Returns the implementation's end of the pipe.
+    fn handle(&self) -> PipeHandle {
+        PipeHandle::new(
+            FileDescriptor::Wildcard(self.read.as_raw_fd() as usize),
+            FileDescriptor::Wildcard(self.write.as_raw_fd() as usize),
+        )
+    }
+
+    /// Closes the implementation's end of the pipe, and returns the bytes that it wrote.
+    fn finish(self) -> Vec<u8> {
+        drop(self.read);
+        drop(self.write);
+        self.feeder.join().unwrap();
+        self.collector.join().unwrap()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_conformance_in_memory() {
+    for transcript in Transcript::load_all() {
+        let name = &transcript.name;
+
+        if transcript.checks_client() {
+            for read_method in [ReadMethod::Get, ReadMethod::Chunked(7)] {
+                let hint = MemoryChannel::new(transcript.bytes(Stream::Hint, Direction::ToClient));
+                let preimage =
+                    MemoryChannel::new(transcript.bytes(Stream::Preimage, Direction::ToClient));
+                replay_client(&transcript, hint.clone(), preimage.clone(), read_method).await;
+
+                assert_eq!(
+                    hint.written(),
+                    transcript.bytes(Stream::Hint, Direction::ToHost),
+                    "{name}"
+                );
+                assert_eq!(
+                    preimage.written(),
+                    transcript.bytes(Stream::Preimage, Direction::ToHost),
+                    "{name}"
+                );
+            }
+        }
+
+        if transcript.checks_host() {
+            let hint = MemoryChannel::new(transcript.bytes(Stream::Hint, Direction::ToHost));
+            let preimage =
+                MemoryChannel::new(transcript.bytes(Stream::Preimage, Direction::ToHost));
+            replay_host(&transcript, hint.clone(), preimage.clone()).await;
+
+            assert_eq!(
+                hint.written(),
+                transcript.bytes(Stream::Hint, Direction::ToClient),
+                "{name}"
+            );
+            assert_eq!(
+                preimage.written(),
+                transcript.bytes(Stream::Preimage, Direction::ToClient),
+                "{name}"
+            );
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_conformance_over_fragmented_pipes() {
+    for transcript in Transcript::load_all() {
+        let name = &transcript.name;
+
+        if transcript.checks_client() {
+            let hint = FragmentedPeer::spawn(transcript.bytes(Stream::Hint, Direction::ToClient));
+            let preimage =
+                FragmentedPeer::spawn(transcript.bytes(Stream::Preimage, Direction::ToClient));
+            replay_client(&transcript, hint.handle(), preimage.handle(), ReadMethod::Get).await;
+
+            assert_eq!(hint.finish(), transcript.bytes(Stream::Hint, Direction::ToHost), "{name}");
+            assert_eq!(
+                preimage.finish(),
+                transcript.bytes(Stream::Preimage, Direction::ToHost),
+                "{name}"
+            );
+        }
+
+        if transcript.checks_host() {
+            let hint = FragmentedPeer::spawn(transcript.bytes(Stream::Hint, Direction::ToHost));
+            let preimage =
+                FragmentedPeer::spawn(transcript.bytes(Stream::Preimage, Direction::ToHost));
+            replay_host(&transcript, hint.handle(), preimage.handle()).await;
+
+            assert_eq!(
+                hint.finish(),
+                transcript.bytes(Stream::Hint, Direction::ToClient),
+                "{name}"
+            );
+            assert_eq!(
+                preimage.finish(),
+                transcript.bytes(Stream::Preimage, Direction::ToClient),
+                "{name}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_transcripts_are_well_formed() {
+    for transcript in Transcript::load_all() {
+        let name = &transcript.name;
+        let exchanges = transcript.exchanges();
+        assert!(!exchanges.is_empty(), "{name}: transcript has no requests");
+
+        for (i, (request, response)) in exchanges.into_iter().enumerate() {
+            // The final exchange of a closed transcript may be truncated.
+            if transcript.must_fail(i) {
+                continue;
+            }
+
+            let response = response.unwrap_or_else(|| panic!("{name}: request has no response"));
+            match request.stream {
+                Stream::Hint => {
+                    decode_hint(&request.bytes).unwrap();
+                    assert_eq!(response.bytes.len(), 1, "{name}: invalid hint acknowledgement");
+                }
+                Stream::Preimage => {
+                    let keys = decode_keys(&request.bytes).unwrap();
+                    let preimages = decode_preimages(&response.bytes).unwrap();
+                    assert_eq!(keys.len(), preimages.len(), "{name}: missing preimages");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_transcript_parse_errors() {
+    assert!(Transcript::parse("t", "preimage> 02").is_ok());
+    assert!(Transcript::parse("t", "preimage> 0").is_err());
+    assert!(Transcript::parse("t", "preimage> zz").is_err());
+    assert!(Transcript::parse("t", "request> 02").is_err());
+    assert!(Transcript::parse("t", "eof<\npreimage> 02").is_err());
+    assert!(Transcript::parse("t", "eof< 00").is_err());
+
+    let transcript = Transcript::parse("t", "# comment\n\npreimage> 02 00*3\neof>").unwrap();
+    assert_eq!(transcript.messages[0].bytes, [2, 0, 0, 0]);
+    assert_eq!(transcript.closed, Some(Direction::ToHost));
+}
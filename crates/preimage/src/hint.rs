@@ -1,6 +1,7 @@
 use crate::{
+    batch::{BATCH_REQUEST_ACK, BATCH_REQUEST_HINT},
     traits::{HintRouter, HintWriterClient},
-    HintReaderServer, PipeHandle,
+    Channel, HintReaderServer, PipeHandle,
 };
 use alloc::{boxed::Box, string::String, vec};
 use anyhow::Result;
@@ -10,22 +11,35 @@ use tracing::{error, trace};
 /// A [HintWriter] is a high-level interface to the hint pipe. It provides a way to write hints to
 /// the host.
 #[derive(Debug, Clone, Copy)]
-pub struct HintWriter {
-    pipe_handle: PipeHandle,
+pub struct HintWriter<C = PipeHandle> {
+    channel: C,
 }
 
-impl HintWriter {
-    /// Create a new [HintWriter] from a [PipeHandle].
-    pub const fn new(pipe_handle: PipeHandle) -> Self {
-        Self { pipe_handle }
+impl<C> HintWriter<C> {
+    /// Create a new [HintWriter] from a [Channel].
+    pub const fn new(channel: C) -> Self {
+        Self { channel }
     }
 }
 
-#[async_trait]
-impl HintWriterClient for HintWriter {
-    /// Write a hint to the host. This will overwrite any existing hint in the pipe, and block until
-    /// all data has been written.
-    async fn write(&self, hint: &str) -> Result<()> {
+impl<C: Channel + Send + Sync> HintWriter<C> {
+    /// Negotiates batched preimage requests with the host, by sending the [BATCH_REQUEST_HINT].
+    ///
+    /// # Returns
+    /// - `Ok(true)` if the host accepted batched preimage requests, after which the [OracleReader]
+    ///   may send them. See [OracleReader::with_batch_requests].
+    /// - `Ok(false)` if the host or FPVM does not support batched preimage requests.
+    /// - `Err(_)` if the hint could not be written to the host.
+    ///
+    /// [OracleReader]: crate::OracleReader
+    /// [OracleReader::with_batch_requests]: crate::OracleReader::with_batch_requests
+    pub async fn negotiate_batch_requests(&self) -> Result<bool> {
+        let ack = self.write_hint(BATCH_REQUEST_HINT).await?;
+        Ok(ack == BATCH_REQUEST_ACK)
+    }
+
+    /// Writes a hint to the host, and returns the host's acknowledgement.
+    async fn write_hint(&self, hint: &str) -> Result<u8> {
         // Form the hint into a byte buffer. The format is a 4-byte big-endian length prefix
         // followed by the hint string.
         let mut hint_bytes = vec![0u8; hint.len() + 4];
@@ -35,64 +49,92 @@ impl HintWriterClient for HintWriter {
         trace!(target: "hint_writer", "Writing hint \"{hint}\"");
 
         // Write the hint to the host.
-        self.pipe_handle.write(&hint_bytes).await?;
+        self.channel.write(&hint_bytes).await?;
 
         trace!(target: "hint_writer", "Successfully wrote hint");
 
         // Read the hint acknowledgement from the host.
         let mut hint_ack = [0u8; 1];
-        self.pipe_handle.read_exact(&mut hint_ack).await?;
+        self.channel.read_exact(&mut hint_ack).await?;
 
         trace!(target: "hint_writer", "Received hint acknowledgement");
 
-        Ok(())
+        Ok(hint_ack[0])
+    }
+}
+
+#[async_trait]
+impl<C: Channel + Send + Sync> HintWriterClient for HintWriter<C> {
+    /// Write a hint to the host. This will overwrite any existing hint in the pipe, and block until
+    /// all data has been written.
+    async fn write(&self, hint: &str) -> Result<()> {
+        self.write_hint(hint).await.map(|_| ())
     }
 }
 
 /// A [HintReader] is a router for hints sent by the [HintWriter] from the client program. It
 /// provides a way for the host to prepare preimages for reading.
 #[derive(Debug, Clone, Copy)]
-pub struct HintReader {
-    pipe_handle: PipeHandle,
+pub struct HintReader<C = PipeHandle> {
+    channel: C,
+    /// Whether the host accepts batched preimage requests.
+    batch_requests: bool,
 }
 
-impl HintReader {
-    /// Create a new [HintReader] from a [PipeHandle].
-    pub fn new(pipe_handle: PipeHandle) -> Self {
-        Self { pipe_handle }
+impl<C> HintReader<C> {
+    /// Create a new [HintReader] from a [Channel].
+    pub fn new(channel: C) -> Self {
+        Self { channel, batch_requests: false }
+    }
+
+    /// Sets whether the [HintReader] accepts batched preimage requests on behalf of the host. If
+    /// enabled, the [BATCH_REQUEST_HINT] is acknowledged as accepted rather than routed. The
+    /// [OracleServer] always serves batched requests.
+    ///
+    /// [OracleServer]: crate::OracleServer
+    pub fn with_batch_requests(mut self, enabled: bool) -> Self {
+        self.batch_requests = enabled;
+        self
     }
 }
 
 #[async_trait]
-impl HintReaderServer for HintReader {
+impl<C: Channel + Send + Sync> HintReaderServer for HintReader<C> {
     async fn next_hint<R>(&self, hint_router: &R) -> Result<()>
     where
         R: HintRouter + Send + Sync,
     {
         // Read the length of the raw hint payload.
         let mut len_buf = [0u8; 4];
-        self.pipe_handle.read_exact(&mut len_buf).await?;
+        self.channel.read_exact(&mut len_buf).await?;
         let len = u32::from_be_bytes(len_buf);
 
         // Read the raw hint payload.
         let mut raw_payload = vec![0u8; len as usize];
-        self.pipe_handle.read_exact(raw_payload.as_mut_slice()).await?;
+        self.channel.read_exact(raw_payload.as_mut_slice()).await?;
         let payload = String::from_utf8(raw_payload)
             .map_err(|e| anyhow::anyhow!("Failed to decode hint payload: {e}"))?;
 
         trace!(target: "hint_reader", "Successfully read hint: \"{payload}\"");
 
+        // Accept batched preimage requests if the host supports them.
+        if self.batch_requests && payload == BATCH_REQUEST_HINT {
+            self.channel.write(&[BATCH_REQUEST_ACK]).await?;
+            trace!(target: "hint_reader", "Accepted batched preimage requests");
+            return Ok(());
+        }
+
         // Route the hint
         if let Err(e) = hint_router.route_hint(payload).await {
             // Write back on error to prevent blocking the client.
-            self.pipe_handle.write(&[0x00]).await?;
+            self.channel.write(&[0x00]).await?;
 
             error!("Failed to route hint: {e}");
             anyhow::bail!("Failed to rout hint: {e}");
         }
 
         // Write back an acknowledgement to the client to unblock their process.
-        self.pipe_handle.write(&[0x00]).await?;
+        self.channel.write(&[0x00]).await?;
 
         trace!(target: "hint_reader", "Successfully routed and acknowledged hint");
 
@@ -158,4 +200,45 @@ mod test {
         let h = hints.remove(0);
         assert_eq!(h, MOCK_DATA);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_negotiate_batch_requests() {
+        for host_supports_batching in [true, false] {
+            let incoming_hints = Arc::new(Mutex::new(Vec::new()));
+            let hint_pipe = bidirectional_pipe().unwrap();
+
+            let client = tokio::task::spawn(async move {
+                let hint_writer = HintWriter::new(PipeHandle::new(
+                    FileDescriptor::Wildcard(hint_pipe.client.read.as_raw_fd() as usize),
+                    FileDescriptor::Wildcard(hint_pipe.client.write.as_raw_fd() as usize),
+                ));
+
+                hint_writer.negotiate_batch_requests().await.unwrap()
+            });
+            let host = tokio::task::spawn({
+                let incoming_hints_ref = Arc::clone(&incoming_hints);
+                async move {
+                    let router = TestRouter { incoming_hints: incoming_hints_ref };
+
+                    let hint_reader = HintReader::new(PipeHandle::new(
+                        FileDescriptor::Wildcard(hint_pipe.host.read.as_raw_fd() as usize),
+                        FileDescriptor::Wildcard(hint_pipe.host.write.as_raw_fd() as usize),
+                    ))
+                    .with_batch_requests(host_supports_batching);
+                    hint_reader.next_hint(&router).await.unwrap();
+                }
+            });
+
+            let (negotiated, _) = tokio::join!(client, host);
+            assert_eq!(negotiated.unwrap(), host_supports_batching);
+
+            // Hosts that do not support batching route the hint like any other.
+            let hints = incoming_hints.lock().await;
+            if host_supports_batching {
+                assert!(hints.is_empty());
+            } else {
+                assert_eq!(*hints, [BATCH_REQUEST_HINT]);
+            }
+        }
+    }
 }
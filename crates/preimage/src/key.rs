@@ -1,11 +1,12 @@
 //! Contains the [PreimageKey] type, which is used to identify preimages that may be fetched from
 //! the preimage oracle.
 
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{keccak256, B256, U256};
 #[cfg(feature = "rkyv")]
 use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 #[cfg(feature = "serde")]
 use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+use sha2::{Digest, Sha256};
 
 /// <https://specs.optimism.io/experimental/fault-proof/index.html#pre-image-key-types>
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
@@ -21,7 +22,9 @@ pub enum PreimageKeyType {
     /// low-order 31 bytes of the preimage's `keccak256` digest to the preimage itself.
     #[default]
     Keccak256 = 2,
-    /// GlobalGeneric key types are reserved for future use.
+    /// GlobalGeneric key types are global and context independent. They allow new kinds of data to
+    /// be content-addressed without minting a new key type: the key commits to the preimage with
+    /// the [GlobalGenericHash] that is tagged within it. See [PreimageKey::new_global_generic].
     GlobalGeneric = 3,
     /// Sha256 key types are global and context independent. Preimages are mapped from the
     /// low-order 31 bytes of the preimage's `sha256` digest to the preimage itself.
@@ -52,6 +55,41 @@ impl TryFrom<u8> for PreimageKeyType {
     }
 }
 
+/// The hash functions that a [PreimageKeyType::GlobalGeneric] key may commit to its preimage with.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[repr(u8)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
+#[cfg_attr(feature = "serde", derive(SerdeSerialize, SerdeDeserialize))]
+pub enum GlobalGenericHash {
+    /// The `keccak256` hash function.
+    Keccak256 = 1,
+    /// The `sha256` hash function.
+    Sha256 = 2,
+}
+
+impl GlobalGenericHash {
+    /// Returns the digest of the given data under the hash function.
+    pub fn digest(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Keccak256 => *keccak256(data),
+            Self::Sha256 => Sha256::digest(data).into(),
+        }
+    }
+}
+
+impl TryFrom<u8> for GlobalGenericHash {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => GlobalGenericHash::Keccak256,
+            2 => GlobalGenericHash::Sha256,
+            _ => anyhow::bail!("Invalid global generic hash function"),
+        })
+    }
+}
+
 /// A preimage key is a 32-byte value that identifies a preimage that may be fetched from the
 /// oracle.
 ///
@@ -60,6 +98,14 @@ impl TryFrom<u8> for PreimageKeyType {
 /// |---------|-------------|
 /// | [0, 1)  | Type byte   |
 /// | [1, 32) | Data        |
+///
+/// The data of a [PreimageKeyType::GlobalGeneric] key is further split into the tag of its
+/// [GlobalGenericHash] and the digest of the preimage:
+/// |  Bits   | Description                       |
+/// |---------|-----------------------------------|
+/// | [0, 1)  | Type byte                         |
+/// | [1, 2)  | Hash function tag                 |
+/// | [2, 32) | Low-order 30 bytes of the digest  |
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
 #[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
@@ -86,6 +132,30 @@ impl PreimageKey {
         Self { data, key_type: PreimageKeyType::Local }
     }
 
+    /// Creates a new [PreimageKeyType::GlobalGeneric] key from the digest of a preimage under the
+    /// given [GlobalGenericHash]. The digest will be truncated to its low-order 30 bytes.
+    pub fn new_global_generic(hash: GlobalGenericHash, digest: [u8; 32]) -> Self {
+        let mut data = [0u8; 31];
+        data[0] = hash as u8;
+        data[1..].copy_from_slice(&digest[2..]);
+        Self { data, key_type: PreimageKeyType::GlobalGeneric }
+    }
+
+    /// Creates the [PreimageKeyType::GlobalGeneric] key that commits to the given preimage under
+    /// the given [GlobalGenericHash].
+    pub fn global_generic(hash: GlobalGenericHash, preimage: &[u8]) -> Self {
+        Self::new_global_generic(hash, hash.digest(preimage))
+    }
+
+    /// Returns the [GlobalGenericHash] that a [PreimageKeyType::GlobalGeneric] key commits to its
+    /// preimage with, or [None] if the key is of another type or its hash function is unknown.
+    pub fn global_generic_hash(&self) -> Option<GlobalGenericHash> {
+        if self.key_type != PreimageKeyType::GlobalGeneric {
+            return None;
+        }
+        GlobalGenericHash::try_from(self.data[0]).ok()
+    }
+
     /// Returns the [PreimageKeyType] for the [PreimageKey].
     pub fn key_type(&self) -> PreimageKeyType {
         self.key_type
@@ -154,4 +224,29 @@ mod test {
             assert_eq!(actual, rendered_key);
         }
     }
+
+    #[test]
+    fn test_global_generic_keys() {
+        const PREIMAGE: &[u8] = b"hello world";
+
+        let key = PreimageKey::global_generic(GlobalGenericHash::Keccak256, PREIMAGE);
+        let rendered: [u8; 32] = key.into();
+        assert_eq!(rendered[0], PreimageKeyType::GlobalGeneric as u8);
+        assert_eq!(rendered[1], GlobalGenericHash::Keccak256 as u8);
+        assert_eq!(rendered[2..], keccak256(PREIMAGE)[2..]);
+        assert_eq!(key.global_generic_hash(), Some(GlobalGenericHash::Keccak256));
+        assert_eq!(PreimageKey::try_from(rendered).unwrap(), key);
+
+        let key = PreimageKey::global_generic(GlobalGenericHash::Sha256, PREIMAGE);
+        let rendered: [u8; 32] = key.into();
+        assert_eq!(rendered[1], GlobalGenericHash::Sha256 as u8);
+        assert_eq!(rendered[2..], Sha256::digest(PREIMAGE)[2..]);
+        assert_eq!(key.global_generic_hash(), Some(GlobalGenericHash::Sha256));
+
+        // Keys of other types, and global generic keys with unknown hash functions, have no hash.
+        let keccak_key = PreimageKey::new(*keccak256(PREIMAGE), PreimageKeyType::Keccak256);
+        assert_eq!(keccak_key.global_generic_hash(), None);
+        let unknown_key = PreimageKey::new([0xFF; 32], PreimageKeyType::GlobalGeneric);
+        assert_eq!(unknown_key.global_generic_hash(), None);
+    }
 }
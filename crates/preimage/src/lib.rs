@@ -7,8 +7,11 @@
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 mod key;
-pub use key::{PreimageKey, PreimageKeyType};
+pub use key::{GlobalGenericHash, PreimageKey, PreimageKeyType};
 
 mod oracle;
 pub use oracle::{OracleReader, OracleServer};
@@ -16,14 +19,23 @@ pub use oracle::{OracleReader, OracleServer};
 mod hint;
 pub use hint::{HintReader, HintWriter};
 
+mod batch;
+pub use batch::{BATCH_REQUEST_HINT, MAX_BATCH_SIZE};
+
 mod pipe;
 pub use pipe::PipeHandle;
 
 mod traits;
 pub use traits::{
-    CommsClient, HintReaderServer, HintRouter, HintWriterClient, PreimageFetcher,
+    Channel, CommsClient, HintReaderServer, HintRouter, HintWriterClient, PreimageFetcher,
     PreimageOracleClient, PreimageOracleServer,
 };
 
+#[cfg(feature = "std")]
+pub mod transport;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
 #[cfg(test)]
-mod test_utils;
+mod conformance;
@@ -1,39 +1,58 @@
 use crate::{
-    traits::PreimageFetcher, PipeHandle, PreimageKey, PreimageOracleClient, PreimageOracleServer,
+    batch::{decode_batch_header, encode_batch_header, MAX_BATCH_SIZE},
+    traits::PreimageFetcher,
+    Channel, PipeHandle, PreimageKey, PreimageOracleClient, PreimageOracleServer,
 };
 use alloc::{boxed::Box, vec::Vec};
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use tracing::trace;
 
-/// An [OracleReader] is a high-level interface to the preimage oracle.
+/// The maximum number of preimage bytes that the [OracleServer] writes to the channel at once.
+const MAX_CHUNK_SIZE: usize = 32 * 1024;
+
+/// An [OracleReader] is a high-level interface to the preimage oracle. By default, it speaks to
+/// the host over a [PipeHandle], but any [Channel] may be used as the transport.
 #[derive(Debug, Clone, Copy)]
-pub struct OracleReader {
-    pipe_handle: PipeHandle,
+pub struct OracleReader<C = PipeHandle> {
+    channel: C,
+    /// Whether batched preimage requests have been negotiated with the host.
+    batch_requests: bool,
 }
 
-impl OracleReader {
-    /// Create a new [OracleReader] from a [PipeHandle].
-    pub const fn new(pipe_handle: PipeHandle) -> Self {
-        Self { pipe_handle }
+impl<C> OracleReader<C> {
+    /// Create a new [OracleReader] from a [Channel].
+    pub const fn new(channel: C) -> Self {
+        Self { channel, batch_requests: false }
     }
 
+    /// Sets whether the [OracleReader] sends batched preimage requests. This must only be enabled
+    /// once the host has accepted them through [HintWriter::negotiate_batch_requests].
+    ///
+    /// [HintWriter::negotiate_batch_requests]: crate::HintWriter::negotiate_batch_requests
+    pub const fn with_batch_requests(mut self, enabled: bool) -> Self {
+        self.batch_requests = enabled;
+        self
+    }
+}
+
+impl<C: Channel + Send + Sync> OracleReader<C> {
     /// Set the preimage key for the global oracle reader. This will overwrite any existing key, and
     /// block until the host has prepared the preimage and responded with the length of the
     /// preimage.
     async fn write_key(&self, key: PreimageKey) -> Result<usize> {
         // Write the key to the host so that it can prepare the preimage.
         let key_bytes: [u8; 32] = key.into();
-        self.pipe_handle.write(&key_bytes).await?;
+        self.channel.write(&key_bytes).await?;
 
         // Read the length prefix and reset the cursor.
         let mut length_buffer = [0u8; 8];
-        self.pipe_handle.read_exact(&mut length_buffer).await?;
+        self.channel.read_exact(&mut length_buffer).await?;
         Ok(u64::from_be_bytes(length_buffer) as usize)
     }
 }
 
 #[async_trait::async_trait]
-impl PreimageOracleClient for OracleReader {
+impl<C: Channel + Send + Sync> PreimageOracleClient for OracleReader<C> {
     /// Get the data corresponding to the currently set key from the host. Return the data in a new
     /// heap allocated `Vec<u8>`
     async fn get(&self, key: PreimageKey) -> Result<Vec<u8>> {
@@ -50,7 +69,7 @@ impl PreimageOracleClient for OracleReader {
         trace!(target: "oracle_client", "Reading data from preimage oracle. Key {key}");
 
         // Grab a read lock on the preimage pipe to read the data.
-        self.pipe_handle.read_exact(&mut data_buffer).await?;
+        self.channel.read_exact(&mut data_buffer).await?;
 
         trace!(target: "oracle_client", "Successfully read data from preimage oracle. Key: {key}");
 
@@ -76,50 +95,157 @@ impl PreimageOracleClient for OracleReader {
             return Ok(());
         }
 
-        self.pipe_handle.read_exact(buf).await?;
+        self.channel.read_exact(buf).await?;
 
         trace!(target: "oracle_client", "Successfully read data from preimage oracle. Key: {key}");
 
         Ok(())
     }
+
+    /// Get the data corresponding to the given key from the host, reading it into the provided
+    /// buffer one chunk at a time.
+    async fn get_chunked<F>(
+        &self,
+        key: PreimageKey,
+        buf: &mut [u8],
+        mut on_chunk: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&[u8]) -> Result<()> + Send,
+    {
+        ensure!(!buf.is_empty(), "Chunk buffer must not be empty");
+
+        trace!(target: "oracle_client", "Requesting data from preimage oracle. Key {key}");
+
+        // Write the key to the host and read the length of the preimage.
+        let length = self.write_key(key).await?;
+
+        trace!(target: "oracle_client", "Reading data from preimage oracle in chunks. Key {key}");
+
+        let chunk_size = buf.len();
+        let mut remaining = length;
+        let mut result = Ok(());
+        while remaining > 0 {
+            let chunk = &mut buf[..remaining.min(chunk_size)];
+            self.channel.read_exact(chunk).await?;
+            remaining -= chunk.len();
+
+            // If the caller rejects a chunk, the rest of the preimage is still drained from the
+            // channel so that the next request starts at a key boundary.
+            if result.is_ok() {
+                result = on_chunk(chunk);
+            }
+        }
+        result?;
+
+        trace!(target: "oracle_client", "Successfully read data from preimage oracle. Key: {key}");
+
+        Ok(length)
+    }
+
+    /// Get the data corresponding to each of the given keys from the host. If batched requests
+    /// have been negotiated, the keys are sent in batches of up to [MAX_BATCH_SIZE], and otherwise
+    /// one at a time.
+    async fn get_batch(&self, keys: &[PreimageKey]) -> Result<Vec<Vec<u8>>> {
+        let mut preimages = Vec::with_capacity(keys.len());
+        if !self.batch_requests {
+            for key in keys {
+                preimages.push(self.get(*key).await?);
+            }
+            return Ok(preimages);
+        }
+
+        for batch in keys.chunks(MAX_BATCH_SIZE) {
+            trace!(target: "oracle_client", "Requesting batch of {} preimages", batch.len());
+
+            // Write the batch header followed by the keys, in a single write.
+            let mut request = Vec::with_capacity((batch.len() + 1) * 32);
+            request.extend_from_slice(&encode_batch_header(batch.len()));
+            for key in batch {
+                request.extend_from_slice(&<[u8; 32]>::from(*key));
+            }
+            self.channel.write(&request).await?;
+
+            // Read the length-prefixed preimages, in the order of the keys.
+            for _ in batch {
+                let mut length_buffer = [0u8; 8];
+                self.channel.read_exact(&mut length_buffer).await?;
+                let mut data_buffer = alloc::vec![0; u64::from_be_bytes(length_buffer) as usize];
+                if !data_buffer.is_empty() {
+                    self.channel.read_exact(&mut data_buffer).await?;
+                }
+                preimages.push(data_buffer);
+            }
+
+            trace!(target: "oracle_client", "Successfully read batch of {} preimages", batch.len());
+        }
+
+        Ok(preimages)
+    }
 }
 
 /// An [OracleServer] is a router for the host to serve data back to the client [OracleReader].
 #[derive(Debug, Clone, Copy)]
-pub struct OracleServer {
-    pipe_handle: PipeHandle,
+pub struct OracleServer<C = PipeHandle> {
+    channel: C,
 }
 
-impl OracleServer {
-    /// Create a new [OracleServer] from a [PipeHandle].
-    pub fn new(pipe_handle: PipeHandle) -> Self {
-        Self { pipe_handle }
+impl<C> OracleServer<C> {
+    /// Create a new [OracleServer] from a [Channel].
+    pub fn new(channel: C) -> Self {
+        Self { channel }
     }
 }
 
 #[async_trait::async_trait]
-impl PreimageOracleServer for OracleServer {
+impl<C: Channel + Send + Sync> PreimageOracleServer for OracleServer<C> {
     async fn next_preimage_request<F>(&self, fetcher: &F) -> Result<()>
     where
         F: PreimageFetcher + Send + Sync,
     {
         // Read the preimage request from the client, and throw early if there isn't is any.
         let mut buf = [0u8; 32];
-        self.pipe_handle.read_exact(&mut buf).await?;
-        let preimage_key = PreimageKey::try_from(buf)?;
+        self.channel.read_exact(&mut buf).await?;
+
+        // Serve a batched request if the client sent one. Clients only do so once the host has
+        // accepted batched requests, so standard requests are never mistaken for one.
+        if let Some(count) = decode_batch_header(&buf)? {
+            trace!(target: "oracle_server", "Received batch of {count} preimage requests");
+
+            let mut keys = alloc::vec![0u8; count * 32];
+            self.channel.read_exact(&mut keys).await?;
+            for key in keys.chunks_exact(32) {
+                let preimage_key = PreimageKey::try_from(
+                    <[u8; 32]>::try_from(key).expect("Keys are exactly 32 bytes"),
+                )?;
+                self.serve_preimage(fetcher, preimage_key).await?;
+            }
 
+            trace!(target: "oracle_server", "Successfully wrote batch of {count} preimages");
+            return Ok(());
+        }
+
+        self.serve_preimage(fetcher, PreimageKey::try_from(buf)?).await
+    }
+}
+
+impl<C: Channel + Send + Sync> OracleServer<C> {
+    /// Fetches the preimage for the given key, and writes it to the client.
+    async fn serve_preimage<F>(&self, fetcher: &F, preimage_key: PreimageKey) -> Result<()>
+    where
+        F: PreimageFetcher + Send + Sync,
+    {
         trace!(target: "oracle_server", "Fetching preimage for key {preimage_key}");
 
         // Fetch the preimage value from the preimage getter.
         let value = fetcher.get_preimage(preimage_key).await?;
 
-        // Write the length as a big-endian u64 followed by the data.
-        let data = [(value.len() as u64).to_be_bytes().as_ref(), value.as_ref()]
-            .into_iter()
-            .flatten()
-            .copied()
-            .collect::<Vec<_>>();
-        self.pipe_handle.write(data.as_slice()).await?;
+        // Write the length as a big-endian u64 followed by the data. The data is written in bounded
+        // chunks, so that large preimages are not copied into a single contiguous buffer.
+        self.channel.write(&(value.len() as u64).to_be_bytes()).await?;
+        for chunk in value.chunks(MAX_CHUNK_SIZE) {
+            self.channel.write(chunk).await?;
+        }
 
         trace!(target: "oracle_server", "Successfully wrote preimage data for key {preimage_key}");
 
@@ -199,4 +325,201 @@ mod test {
         assert_eq!(contents_a, MOCK_DATA_A);
         assert_eq!(contents_b, MOCK_DATA_B);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_oracle_client_chunked_reads() {
+        const MOCK_DATA_A: &[u8] = b"1234567890";
+        let mock_data_b = (0..MAX_CHUNK_SIZE * 2 + 1).map(|i| i as u8).collect::<Vec<_>>();
+        let key_a: PreimageKey =
+            PreimageKey::new(*keccak256(MOCK_DATA_A), PreimageKeyType::Keccak256);
+        let key_b: PreimageKey =
+            PreimageKey::new(*keccak256(&mock_data_b), PreimageKeyType::Keccak256);
+        let key_empty: PreimageKey = PreimageKey::new(*keccak256([]), PreimageKeyType::Keccak256);
+
+        let preimages = {
+            let mut preimages = HashMap::new();
+            preimages.insert(key_a, MOCK_DATA_A.to_vec());
+            preimages.insert(key_b, mock_data_b.clone());
+            preimages.insert(key_empty, Vec::new());
+            Arc::new(Mutex::new(preimages))
+        };
+
+        let preimage_pipe = bidirectional_pipe().unwrap();
+
+        let client = tokio::task::spawn(async move {
+            let oracle_reader = OracleReader::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.client.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.client.write.as_raw_fd() as usize),
+            ));
+
+            // The preimage is yielded in chunks no larger than the buffer.
+            let mut buf = [0u8; 4];
+            let mut chunks_a = Vec::new();
+            let length_a = oracle_reader
+                .get_chunked(key_a, &mut buf, |chunk| {
+                    chunks_a.push(chunk.to_vec());
+                    Ok(())
+                })
+                .await
+                .unwrap();
+
+            // Preimages larger than the server's chunk size are reassembled in order.
+            let mut buf = [0u8; 4096];
+            let mut contents_b = Vec::new();
+            let length_b = oracle_reader
+                .get_chunked(key_b, &mut buf, |chunk| {
+                    contents_b.extend_from_slice(chunk);
+                    Ok(())
+                })
+                .await
+                .unwrap();
+
+            // Empty preimages yield no chunks.
+            let length_empty = oracle_reader
+                .get_chunked(key_empty, &mut buf, |_| anyhow::bail!("Unexpected chunk"))
+                .await
+                .unwrap();
+
+            // A rejected chunk fails the read, but the rest of the preimage is drained so that
+            // the next request is served correctly.
+            let mut buf = [0u8; 4];
+            let rejected =
+                oracle_reader.get_chunked(key_a, &mut buf, |_| anyhow::bail!("Rejected")).await;
+            let contents_a = oracle_reader.get(key_a).await.unwrap();
+
+            // An empty buffer is rejected before the key is sent to the host.
+            let empty_buf = oracle_reader.get_chunked(key_a, &mut [], |_| Ok(())).await;
+
+            (
+                chunks_a,
+                length_a,
+                contents_b,
+                length_b,
+                length_empty,
+                rejected,
+                contents_a,
+                empty_buf,
+            )
+        });
+        tokio::task::spawn(async move {
+            let oracle_server = OracleServer::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.host.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.host.write.as_raw_fd() as usize),
+            ));
+            let test_fetcher = TestFetcher { preimages: Arc::clone(&preimages) };
+
+            loop {
+                if oracle_server.next_preimage_request(&test_fetcher).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (c,) = tokio::join!(client);
+        let (
+            chunks_a,
+            length_a,
+            contents_b,
+            length_b,
+            length_empty,
+            rejected,
+            contents_a,
+            empty_buf,
+        ) = c.unwrap();
+        assert_eq!(chunks_a, [b"1234".to_vec(), b"5678".to_vec(), b"90".to_vec()]);
+        assert_eq!(length_a, MOCK_DATA_A.len());
+        assert_eq!(contents_b, mock_data_b);
+        assert_eq!(length_b, mock_data_b.len());
+        assert_eq!(length_empty, 0);
+        assert!(rejected.is_err());
+        assert_eq!(contents_a, MOCK_DATA_A);
+        assert!(empty_buf.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_oracle_client_batched_reads() {
+        const MOCK_DATA_A: &[u8] = b"1234567890";
+        const MOCK_DATA_B: &[u8] = b"FACADE";
+        let key_a: PreimageKey =
+            PreimageKey::new(*keccak256(MOCK_DATA_A), PreimageKeyType::Keccak256);
+        let key_b: PreimageKey =
+            PreimageKey::new(*keccak256(MOCK_DATA_B), PreimageKeyType::Keccak256);
+        let key_empty: PreimageKey = PreimageKey::new(*keccak256([]), PreimageKeyType::Keccak256);
+
+        let preimages = {
+            let mut preimages = HashMap::new();
+            preimages.insert(key_a, MOCK_DATA_A.to_vec());
+            preimages.insert(key_b, MOCK_DATA_B.to_vec());
+            preimages.insert(key_empty, Vec::new());
+            Arc::new(Mutex::new(preimages))
+        };
+
+        // Request more keys than fit in a single batch.
+        let keys = [key_a, key_empty, key_b].repeat(MAX_BATCH_SIZE / 2);
+        let expected = [MOCK_DATA_A, &[], MOCK_DATA_B].repeat(MAX_BATCH_SIZE / 2);
+
+        let preimage_pipe = bidirectional_pipe().unwrap();
+
+        let client = tokio::task::spawn(async move {
+            let oracle_reader = OracleReader::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.client.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.client.write.as_raw_fd() as usize),
+            ));
+
+            let batched = oracle_reader.with_batch_requests(true).get_batch(&keys).await.unwrap();
+            let sequential = oracle_reader.get_batch(&keys).await.unwrap();
+            let single = oracle_reader.with_batch_requests(true).get(key_b).await.unwrap();
+
+            (batched, sequential, single)
+        });
+        tokio::task::spawn(async move {
+            let oracle_server = OracleServer::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.host.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.host.write.as_raw_fd() as usize),
+            ));
+            let test_fetcher = TestFetcher { preimages: Arc::clone(&preimages) };
+
+            loop {
+                if oracle_server.next_preimage_request(&test_fetcher).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (c,) = tokio::join!(client);
+        let (batched, sequential, single) = c.unwrap();
+        assert_eq!(batched, expected);
+        assert_eq!(sequential, expected);
+        assert_eq!(single, MOCK_DATA_B);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_oracle_client_fails_on_server_error() {
+        let key = PreimageKey::new(*keccak256(b"missing"), PreimageKeyType::Keccak256);
+        let preimages = Arc::new(Mutex::new(HashMap::new()));
+
+        let preimage_pipe = bidirectional_pipe().unwrap();
+
+        let client = tokio::task::spawn(async move {
+            let oracle_reader = OracleReader::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.client.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.client.write.as_raw_fd() as usize),
+            ));
+            oracle_reader.get(key).await
+        });
+        tokio::task::spawn(async move {
+            let oracle_server = OracleServer::new(PipeHandle::new(
+                FileDescriptor::Wildcard(preimage_pipe.host.read.as_raw_fd() as usize),
+                FileDescriptor::Wildcard(preimage_pipe.host.write.as_raw_fd() as usize),
+            ));
+            let test_fetcher = TestFetcher { preimages };
+
+            // The server stops serving once the preimage cannot be fetched, closing its end of
+            // the pipe.
+            assert!(oracle_server.next_preimage_request(&test_fetcher).await.is_err());
+        });
+
+        let (c,) = tokio::join!(client);
+        assert!(c.unwrap().is_err());
+    }
 }
@@ -1,7 +1,10 @@
 //! This module contains a rudamentary pipe between two file descriptors, using [kona_common::io]
 //! for reading and writing from the file descriptors.
 
+use crate::Channel;
+use alloc::boxed::Box;
 use anyhow::Result;
+use async_trait::async_trait;
 use core::{
     cell::RefCell,
     cmp::Ordering,
@@ -52,6 +55,21 @@ impl PipeHandle {
     }
 }
 
+#[async_trait]
+impl Channel for PipeHandle {
+    async fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        PipeHandle::read(self, buf)
+    }
+
+    async fn read_exact(&self, buf: &mut [u8]) -> Result<usize> {
+        PipeHandle::read_exact(self, buf).await
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize> {
+        PipeHandle::write(self, buf).await
+    }
+}
+
 /// A future that reads from a pipe, returning [Poll::Ready] when the buffer is full.
 struct ReadFuture<'a> {
     /// The pipe handle to read from
@@ -73,6 +91,16 @@ impl Future for ReadFuture<'_> {
         // Drop the borrow on self.
         drop(buf);
 
+        // A read of zero bytes with data still outstanding indicates that the other end of the
+        // pipe has been closed, and no more data will arrive.
+        if chunk_read == 0 && self.read < buf_len {
+            return Poll::Ready(Err(anyhow::anyhow!(
+                "Pipe closed after reading {} of {} bytes",
+                self.read,
+                buf_len
+            )));
+        }
+
         self.read += chunk_read;
 
         match self.read.cmp(&buf_len) {
@@ -0,0 +1,166 @@
+//! Contains the [FaultyOracle], which injects faults into the responses of an oracle.
+
+use crate::{HintWriterClient, PreimageKey, PreimageOracleClient};
+use alloc::{boxed::Box, vec::Vec};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use hashbrown::HashSet;
+
+/// A wrapper around a [PreimageOracleClient] and [HintWriterClient] that injects faults into its
+/// responses, to test how the consumers of an oracle handle a misbehaving host:
+/// - **Delays**: Every hint and preimage request yields to the executor a number of times before it
+///   is forwarded to the inner oracle, so that concurrent tasks interleave with it.
+/// - **Missing preimages**: Requests for the given keys fail, as if the host could not serve them.
+/// - **Corrupted preimages**: The preimages of the given keys have their last byte flipped, or a
+///   byte appended if they are empty, so that they no longer match their keys.
+///
+/// Batched and chunked requests are served one preimage at a time, so that the faults apply to
+/// each of their keys.
+#[derive(Debug, Clone)]
+pub struct FaultyOracle<O> {
+    /// The wrapped oracle.
+    inner: O,
+    /// The number of times to yield to the executor before each hint and preimage request.
+    delay: usize,
+    /// The keys whose preimages are missing.
+    missing: HashSet<PreimageKey>,
+    /// The keys whose preimages are corrupted.
+    corrupted: HashSet<PreimageKey>,
+}
+
+impl<O> FaultyOracle<O> {
+    /// Creates a new [FaultyOracle] that wraps the given oracle, without any faults.
+    pub fn new(inner: O) -> Self {
+        Self { inner, delay: 0, missing: HashSet::new(), corrupted: HashSet::new() }
+    }
+
+    /// Yields to the executor `yields` times before each hint and preimage request.
+    pub fn with_delay(mut self, yields: usize) -> Self {
+        self.delay = yields;
+        self
+    }
+
+    /// Fails requests for the preimage of the given key.
+    pub fn with_missing(mut self, key: PreimageKey) -> Self {
+        self.missing.insert(key);
+        self
+    }
+
+    /// Corrupts the preimage of the given key.
+    pub fn with_corrupted(mut self, key: PreimageKey) -> Self {
+        self.corrupted.insert(key);
+        self
+    }
+
+    /// Returns the wrapped oracle.
+    pub const fn inner(&self) -> &O {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<O> PreimageOracleClient for FaultyOracle<O>
+where
+    O: PreimageOracleClient + Send + Sync,
+{
+    async fn get(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        YieldNow(self.delay).await;
+
+        if self.missing.contains(&key) {
+            bail!("Preimage not found for key {key}");
+        }
+
+        let mut preimage = self.inner.get(key).await?;
+        if self.corrupted.contains(&key) {
+            match preimage.last_mut() {
+                Some(byte) => *byte ^= 0xFF,
+                None => preimage.push(0xFF),
+            }
+        }
+        Ok(preimage)
+    }
+
+    async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> Result<()> {
+        let preimage = self.get(key).await?;
+        if buf.len() != preimage.len() {
+            bail!("Buffer size {} does not match preimage size {}", buf.len(), preimage.len());
+        }
+        buf.copy_from_slice(&preimage);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<O> HintWriterClient for FaultyOracle<O>
+where
+    O: HintWriterClient + Send + Sync,
+{
+    async fn write(&self, hint: &str) -> Result<()> {
+        YieldNow(self.delay).await;
+        self.inner.write(hint).await
+    }
+}
+
+/// A future that yields to the executor the given number of times before it completes.
+struct YieldNow(usize);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0 == 0 {
+            return Poll::Ready(());
+        }
+        self.0 -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{InMemoryOracle, RecordingOracle};
+    use alloc::vec;
+
+    #[tokio::test]
+    async fn test_faulty_oracle() {
+        let inner = InMemoryOracle::new();
+        let key_a = inner.insert_keccak256(b"hello".to_vec());
+        let key_b = inner.insert_keccak256(b"world".to_vec());
+        let key_c = inner.insert_keccak256(Vec::new());
+        let oracle = FaultyOracle::new(RecordingOracle::new(inner))
+            .with_delay(3)
+            .with_missing(key_b)
+            .with_corrupted(key_a)
+            .with_corrupted(key_c);
+
+        assert_eq!(oracle.get(key_a).await.unwrap(), b"hell\x90");
+        assert_eq!(oracle.get(key_c).await.unwrap(), vec![0xFF]);
+        assert!(oracle.get(key_b).await.is_err());
+        assert!(oracle.get_exact(key_c, &mut []).await.is_err());
+        assert!(oracle.get_batch(&[key_a, key_b]).await.is_err());
+        oracle.write("l1-block-header 0x01").await.unwrap();
+
+        // Missing preimages are never requested from the inner oracle.
+        assert_eq!(oracle.inner().keys(), vec![key_a, key_c, key_c, key_a]);
+        assert_eq!(oracle.inner().hints(), vec!["l1-block-header 0x01"]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_faulty_oracle_delay() {
+        let oracle = FaultyOracle::new(InMemoryOracle::new()).with_delay(2);
+        let hints = oracle.inner().clone();
+
+        // The delayed hint is written after the task that was spawned before it.
+        let task = tokio::spawn(async move { hints.write("first").await.unwrap() });
+        oracle.write("second").await.unwrap();
+        task.await.unwrap();
+        assert_eq!(oracle.inner().hints(), vec!["first", "second"]);
+    }
+}
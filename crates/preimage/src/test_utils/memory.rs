@@ -0,0 +1,125 @@
+//! Contains the [InMemoryOracle], which serves preimages from memory.
+
+use crate::{
+    HintWriterClient, PreimageFetcher, PreimageKey, PreimageKeyType, PreimageOracleClient,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use alloy_primitives::keccak256;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use hashbrown::HashMap;
+use spin::Mutex;
+
+/// An in-memory [PreimageOracleClient] and [HintWriterClient], which serves the preimages that it
+/// holds and collects the hints that are written to it. Clones share the same preimages and hints.
+///
+/// The [InMemoryOracle] is also a [PreimageFetcher], so that it can back an [OracleServer] in
+/// tests that exercise the wire protocol.
+///
+/// [OracleServer]: crate::OracleServer
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryOracle {
+    /// The preimages served by the oracle.
+    preimages: Arc<Mutex<HashMap<PreimageKey, Vec<u8>>>>,
+    /// The hints written to the oracle, in order.
+    hints: Arc<Mutex<Vec<String>>>,
+}
+
+impl InMemoryOracle {
+    /// Creates a new, empty [InMemoryOracle].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the preimage for the given key, replacing any existing preimage.
+    pub fn insert(&self, key: PreimageKey, preimage: Vec<u8>) {
+        self.preimages.lock().insert(key, preimage);
+    }
+
+    /// Inserts the preimage under its [PreimageKeyType::Keccak256] key, and returns the key.
+    pub fn insert_keccak256(&self, preimage: Vec<u8>) -> PreimageKey {
+        let key = PreimageKey::new(*keccak256(&preimage), PreimageKeyType::Keccak256);
+        self.insert(key, preimage);
+        key
+    }
+
+    /// Returns the hints written to the oracle so far, in order.
+    pub fn hints(&self) -> Vec<String> {
+        self.hints.lock().clone()
+    }
+}
+
+impl FromIterator<(PreimageKey, Vec<u8>)> for InMemoryOracle {
+    fn from_iter<I: IntoIterator<Item = (PreimageKey, Vec<u8>)>>(iter: I) -> Self {
+        Self { preimages: Arc::new(Mutex::new(iter.into_iter().collect())), ..Default::default() }
+    }
+}
+
+#[async_trait]
+impl PreimageOracleClient for InMemoryOracle {
+    async fn get(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        self.preimages
+            .lock()
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| anyhow!("Preimage not found for key {key}"))
+    }
+
+    async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> Result<()> {
+        let preimage = self.get(key).await?;
+        if buf.len() != preimage.len() {
+            bail!("Buffer size {} does not match preimage size {}", buf.len(), preimage.len());
+        }
+        buf.copy_from_slice(&preimage);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HintWriterClient for InMemoryOracle {
+    async fn write(&self, hint: &str) -> Result<()> {
+        self.hints.lock().push(hint.to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PreimageFetcher for InMemoryOracle {
+    async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        self.get(key).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[tokio::test]
+    async fn test_in_memory_oracle() {
+        let oracle = InMemoryOracle::new();
+        let key = oracle.insert_keccak256(b"hello".to_vec());
+        let missing = PreimageKey::new([0xFF; 32], PreimageKeyType::Keccak256);
+
+        // Clones share the preimages and hints.
+        let clone = oracle.clone();
+        clone.write("l1-block-header 0x01").await.unwrap();
+        assert_eq!(clone.get(key).await.unwrap(), b"hello");
+        assert_eq!(oracle.hints(), vec!["l1-block-header 0x01"]);
+
+        let mut buf = [0u8; 5];
+        oracle.get_exact(key, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+        assert!(oracle.get_exact(key, &mut [0u8; 4]).await.is_err());
+        assert!(oracle.get(missing).await.is_err());
+        assert_eq!(oracle.get_preimage(key).await.unwrap(), b"hello");
+
+        let oracle = InMemoryOracle::from_iter([(missing, vec![0x01])]);
+        assert_eq!(oracle.get_batch(&[missing, missing]).await.unwrap(), vec![vec![0x01]; 2]);
+    }
+}
@@ -0,0 +1,20 @@
+//! Test utilities for the `kona-preimage` crate, and for downstream crates that read from the
+//! preimage oracle. Enabled with the `test-utils` feature.
+//!
+//! - [InMemoryOracle] serves preimages from memory, and collects the hints written to it.
+//! - [RecordingOracle] wraps an oracle, and records every hint and preimage key passed to it.
+//! - [FaultyOracle] wraps an oracle, and injects delays, missing preimages and corrupted data.
+
+mod memory;
+pub use memory::InMemoryOracle;
+
+mod recording;
+pub use recording::{OracleEvent, RecordingOracle};
+
+mod faulty;
+pub use faulty::FaultyOracle;
+
+#[cfg(test)]
+mod pipe;
+#[cfg(test)]
+pub(crate) use pipe::bidirectional_pipe;
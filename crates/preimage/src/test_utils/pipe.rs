@@ -1,4 +1,4 @@
-//! Test utilities for the `kona-preimage` crate.
+//! Contains the OS pipes that the tests of the `kona-preimage` crate communicate over.
 
 use anyhow::{anyhow, Result};
 use os_pipe::{PipeReader, PipeWriter};
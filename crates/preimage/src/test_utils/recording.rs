@@ -0,0 +1,153 @@
+//! Contains the [RecordingOracle], which records the hints and preimage keys passed to an oracle.
+
+use crate::{HintWriterClient, PreimageKey, PreimageOracleClient};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use spin::Mutex;
+
+/// An interaction with an oracle, recorded by a [RecordingOracle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OracleEvent {
+    /// A hint was written to the oracle.
+    Hint(String),
+    /// A preimage was requested from the oracle.
+    Preimage(PreimageKey),
+}
+
+/// A wrapper around a [PreimageOracleClient] and [HintWriterClient] that records every hint and
+/// preimage key passed to it, in order, before forwarding them to the inner oracle. Requests that
+/// the inner oracle fails are recorded as well. Clones share the same recording.
+#[derive(Debug, Clone)]
+pub struct RecordingOracle<O> {
+    /// The wrapped oracle.
+    inner: O,
+    /// The recorded interactions with the oracle.
+    events: Arc<Mutex<Vec<OracleEvent>>>,
+}
+
+impl<O> RecordingOracle<O> {
+    /// Creates a new [RecordingOracle] that wraps the given oracle.
+    pub fn new(inner: O) -> Self {
+        Self { inner, events: Default::default() }
+    }
+
+    /// Returns the wrapped oracle.
+    pub const fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns every interaction with the oracle so far, in order.
+    pub fn events(&self) -> Vec<OracleEvent> {
+        self.events.lock().clone()
+    }
+
+    /// Returns the hints written to the oracle so far, in order.
+    pub fn hints(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .iter()
+            .filter_map(|event| match event {
+                OracleEvent::Hint(hint) => Some(hint.clone()),
+                OracleEvent::Preimage(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the keys of the preimages requested from the oracle so far, in order.
+    pub fn keys(&self) -> Vec<PreimageKey> {
+        self.events
+            .lock()
+            .iter()
+            .filter_map(|event| match event {
+                OracleEvent::Preimage(key) => Some(*key),
+                OracleEvent::Hint(_) => None,
+            })
+            .collect()
+    }
+
+    /// Records an interaction with the oracle.
+    fn record(&self, event: OracleEvent) {
+        self.events.lock().push(event);
+    }
+}
+
+#[async_trait]
+impl<O> PreimageOracleClient for RecordingOracle<O>
+where
+    O: PreimageOracleClient + Send + Sync,
+{
+    async fn get(&self, key: PreimageKey) -> Result<Vec<u8>> {
+        self.record(OracleEvent::Preimage(key));
+        self.inner.get(key).await
+    }
+
+    async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> Result<()> {
+        self.record(OracleEvent::Preimage(key));
+        self.inner.get_exact(key, buf).await
+    }
+
+    async fn get_chunked<F>(&self, key: PreimageKey, buf: &mut [u8], on_chunk: F) -> Result<usize>
+    where
+        F: FnMut(&[u8]) -> Result<()> + Send,
+    {
+        self.record(OracleEvent::Preimage(key));
+        self.inner.get_chunked(key, buf, on_chunk).await
+    }
+
+    async fn get_batch(&self, keys: &[PreimageKey]) -> Result<Vec<Vec<u8>>> {
+        self.events.lock().extend(keys.iter().copied().map(OracleEvent::Preimage));
+        self.inner.get_batch(keys).await
+    }
+}
+
+#[async_trait]
+impl<O> HintWriterClient for RecordingOracle<O>
+where
+    O: HintWriterClient + Send + Sync,
+{
+    async fn write(&self, hint: &str) -> Result<()> {
+        self.record(OracleEvent::Hint(hint.to_string()));
+        self.inner.write(hint).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_utils::InMemoryOracle, PreimageKeyType};
+    use alloc::vec;
+
+    #[tokio::test]
+    async fn test_recording_oracle() {
+        let inner = InMemoryOracle::new();
+        let key = inner.insert_keccak256(b"hello".to_vec());
+        let missing = PreimageKey::new([0xFF; 32], PreimageKeyType::Keccak256);
+        let oracle = RecordingOracle::new(inner);
+
+        oracle.write("l1-block-header 0x01").await.unwrap();
+        oracle.get(key).await.unwrap();
+        assert!(oracle.get(missing).await.is_err());
+        oracle.get_chunked(key, &mut [0u8; 2], |_| Ok(())).await.unwrap();
+        oracle.get_batch(&[key, key]).await.unwrap();
+
+        assert_eq!(
+            oracle.events(),
+            vec![
+                OracleEvent::Hint("l1-block-header 0x01".to_string()),
+                OracleEvent::Preimage(key),
+                OracleEvent::Preimage(missing),
+                OracleEvent::Preimage(key),
+                OracleEvent::Preimage(key),
+                OracleEvent::Preimage(key),
+            ]
+        );
+        assert_eq!(oracle.hints(), oracle.inner().hints());
+        assert_eq!(oracle.keys(), vec![key, missing, key, key, key]);
+    }
+}
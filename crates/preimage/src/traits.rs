@@ -1,8 +1,28 @@
 use crate::PreimageKey;
 use alloc::{boxed::Box, string::String, vec::Vec};
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use async_trait::async_trait;
 
+/// A [Channel] is a bidirectional, byte-oriented transport between the client and the host. The
+/// hint and preimage protocols are spoken over a pair of [Channel]s, regardless of the underlying
+/// medium.
+#[async_trait]
+pub trait Channel {
+    /// Reads bytes from the channel into the given buffer, returning the number of bytes read. A
+    /// return value of `0` indicates that the other end of the channel has been closed.
+    async fn read(&self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Reads exactly `buf.len()` bytes from the channel into the given buffer.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the buffer was filled.
+    /// - `Err(_)` if the channel was closed before the buffer could be filled.
+    async fn read_exact(&self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Writes the full buffer to the channel, returning the number of bytes written.
+    async fn write(&self, buf: &[u8]) -> Result<usize>;
+}
+
 /// A [PreimageOracleClient] is a high-level interface to read data from the host, keyed by a
 /// [PreimageKey].
 #[async_trait]
@@ -22,6 +42,52 @@ pub trait PreimageOracleClient {
     /// - `Ok(())` if the data was successfully written into the buffer.
     /// - `Err(_)` if the data could not be written into the buffer.
     async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> Result<()>;
+
+    /// Get the data corresponding to the given key from the host in chunks of at most `buf.len()`
+    /// bytes, passing each chunk to `on_chunk` in order. Unlike [Self::get], this allows large
+    /// preimages to be consumed without holding the full preimage in memory.
+    ///
+    /// The default implementation fetches the full preimage with [Self::get] and chunks it.
+    /// Implementors that read from a transport should override it to stream the data.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` with the total length of the preimage, if every chunk was read and accepted.
+    /// - `Err(_)` if the buffer is empty, the data could not be fetched from the host, or
+    ///   `on_chunk` returned an error.
+    async fn get_chunked<F>(
+        &self,
+        key: PreimageKey,
+        buf: &mut [u8],
+        mut on_chunk: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&[u8]) -> Result<()> + Send,
+    {
+        ensure!(!buf.is_empty(), "Chunk buffer must not be empty");
+
+        let value = self.get(key).await?;
+        for chunk in value.chunks(buf.len()) {
+            on_chunk(chunk)?;
+        }
+        Ok(value.len())
+    }
+
+    /// Get the data corresponding to each of the given keys from the host, in order. Clients that
+    /// have negotiated batched requests with the host fetch all of them in as few round trips as
+    /// possible.
+    ///
+    /// The default implementation fetches each preimage with [Self::get].
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Vec<u8>>)` with the preimage of each key, if all of them were fetched.
+    /// - `Err(_)` if any of the preimages could not be fetched from the host.
+    async fn get_batch(&self, keys: &[PreimageKey]) -> Result<Vec<Vec<u8>>> {
+        let mut preimages = Vec::with_capacity(keys.len());
+        for key in keys {
+            preimages.push(self.get(*key).await?);
+        }
+        Ok(preimages)
+    }
 }
 
 /// A [HintWriterClient] is a high-level interface to the hint pipe. It provides a way to write
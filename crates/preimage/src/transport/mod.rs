@@ -0,0 +1,317 @@
+//! This module contains transports that carry the hint and preimage protocols over a socket, rather
+//! than over a pair of anonymous pipes. They allow the host to run as a long-lived daemon that
+//! clients connect to.
+//!
+//! Both channels of a session are multiplexed over a single connection by a [StreamTransport].
+
+use alloc::{format, string::String};
+use anyhow::{anyhow, bail, Result};
+use core::{fmt::Display, str::FromStr};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+mod stream;
+pub use stream::{StreamChannel, StreamTransport, TransportStream, MAX_FRAME_SIZE};
+
+/// The address of a transport endpoint.
+///
+/// Addresses are written as `unix:<path>` for Unix domain sockets, and `tcp:<ip>:<port>` for TCP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportAddr {
+    /// The path of a Unix domain socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// The address of a TCP socket.
+    Tcp(SocketAddr),
+}
+
+impl FromStr for TransportAddr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            #[cfg(unix)]
+            Some(("unix", path)) if !path.is_empty() => Ok(Self::Unix(PathBuf::from(path))),
+            Some(("tcp", addr)) => Ok(Self::Tcp(
+                addr.parse().map_err(|e| anyhow!("Invalid TCP address \"{addr}\": {e}"))?,
+            )),
+            _ => bail!("Invalid transport address \"{s}\", expected `unix:<path>` or `tcp:<addr>`"),
+        }
+    }
+}
+
+impl Display for TransportAddr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            Self::Tcp(addr) => write!(f, "tcp:{addr}"),
+        }
+    }
+}
+
+/// A [TransportListener] accepts incoming client connections on a [TransportAddr].
+#[derive(Debug)]
+pub enum TransportListener {
+    /// A Unix domain socket listener.
+    #[cfg(unix)]
+    Unix(UnixListener),
+    /// A TCP listener.
+    Tcp(TcpListener),
+}
+
+impl TransportListener {
+    /// Binds a new [TransportListener] to the given address.
+    pub fn bind(addr: &TransportAddr) -> Result<Self> {
+        match addr {
+            #[cfg(unix)]
+            TransportAddr::Unix(path) => Ok(Self::Unix(
+                UnixListener::bind(path).map_err(|e| anyhow!("Failed to bind to {addr}: {e}"))?,
+            )),
+            TransportAddr::Tcp(socket_addr) => Ok(Self::Tcp(
+                TcpListener::bind(socket_addr)
+                    .map_err(|e| anyhow!("Failed to bind to {addr}: {e}"))?,
+            )),
+        }
+    }
+
+    /// Returns the address that the listener is bound to.
+    pub fn local_addr(&self) -> Result<TransportAddr> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener) => listener
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|p| TransportAddr::Unix(p.into())))
+                .ok_or_else(|| anyhow!("Unix listener is not bound to a path")),
+            Self::Tcp(listener) => listener
+                .local_addr()
+                .map(TransportAddr::Tcp)
+                .map_err(|e| anyhow!("Failed to read local address: {e}")),
+        }
+    }
+
+    /// Blocks until a client connects, and returns the [StreamTransport] for the connection along
+    /// with a description of the peer.
+    pub fn accept(&self) -> Result<(StreamTransport, String)> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, peer) =
+                    listener.accept().map_err(|e| anyhow!("Failed to accept connection: {e}"))?;
+                let peer = peer
+                    .as_pathname()
+                    .map_or_else(|| "unix:<unnamed>".into(), |p| format!("unix:{}", p.display()));
+                Ok((StreamTransport::new(stream)?, peer))
+            }
+            Self::Tcp(listener) => {
+                let (stream, peer) =
+                    listener.accept().map_err(|e| anyhow!("Failed to accept connection: {e}"))?;
+                configure_tcp_stream(&stream)?;
+                Ok((StreamTransport::new(stream)?, format!("tcp:{peer}")))
+            }
+        }
+    }
+}
+
+/// Connects to a host listening on the given address, and returns the [StreamTransport] for the
+/// connection.
+pub fn connect(addr: &TransportAddr) -> Result<StreamTransport> {
+    match addr {
+        #[cfg(unix)]
+        TransportAddr::Unix(path) => StreamTransport::new(
+            UnixStream::connect(path).map_err(|e| anyhow!("Failed to connect to {addr}: {e}"))?,
+        ),
+        TransportAddr::Tcp(socket_addr) => {
+            let stream = TcpStream::connect(socket_addr)
+                .map_err(|e| anyhow!("Failed to connect to {addr}: {e}"))?;
+            configure_tcp_stream(&stream)?;
+            StreamTransport::new(stream)
+        }
+    }
+}
+
+/// Disables Nagle's algorithm on the stream. The protocols consist of small, strictly alternating
+/// requests and responses, which would otherwise be delayed.
+fn configure_tcp_stream(stream: &TcpStream) -> Result<()> {
+    stream.set_nodelay(true).map_err(|e| anyhow!("Failed to configure TCP stream: {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Channel, HintReader, HintReaderServer, HintRouter, HintWriter, HintWriterClient,
+        OracleReader, OracleServer, PreimageFetcher, PreimageKey, PreimageKeyType,
+        PreimageOracleClient, PreimageOracleServer,
+    };
+    use alloc::{boxed::Box, string::ToString, sync::Arc, vec, vec::Vec};
+    use alloy_primitives::keccak256;
+    use async_trait::async_trait;
+    use std::io::{Read, Write};
+    use tokio::sync::Mutex;
+
+    const MOCK_HINT: &str = "test-hint 0xfacade";
+    const MOCK_DATA: &[u8] = b"1234567890";
+
+    struct TestRouter {
+        incoming_hints: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl HintRouter for TestRouter {
+        async fn route_hint(&self, hint: String) -> Result<()> {
+            self.incoming_hints.lock().await.push(hint);
+            Ok(())
+        }
+    }
+
+    struct TestFetcher;
+
+    #[async_trait]
+    impl PreimageFetcher for TestFetcher {
+        async fn get_preimage(&self, key: PreimageKey) -> Result<Vec<u8>> {
+            (key == PreimageKey::new(*keccak256(MOCK_DATA), PreimageKeyType::Keccak256))
+                .then(|| MOCK_DATA.to_vec())
+                .ok_or_else(|| anyhow!("Key not found"))
+        }
+    }
+
+    /// Runs a client session against a host over the given transports, and returns the hints
+    /// received by the host and the preimage received by the client.
+    async fn run_session(client: StreamTransport, host: StreamTransport) -> (Vec<String>, Vec<u8>) {
+        let incoming_hints = Arc::new(Mutex::new(Vec::new()));
+
+        let hint_reader = HintReader::new(host.hint_channel());
+        let oracle_server = OracleServer::new(host.preimage_channel());
+        let router = TestRouter { incoming_hints: incoming_hints.clone() };
+        tokio::task::spawn(async move { while hint_reader.next_hint(&router).await.is_ok() {} });
+        tokio::task::spawn(async move {
+            while oracle_server.next_preimage_request(&TestFetcher).await.is_ok() {}
+        });
+
+        let data = tokio::task::spawn(async move {
+            let hint_writer = HintWriter::new(client.hint_channel());
+            let oracle_reader = OracleReader::new(client.preimage_channel());
+            hint_writer.write(MOCK_HINT).await.unwrap();
+            let data = oracle_reader
+                .get(PreimageKey::new(*keccak256(MOCK_DATA), PreimageKeyType::Keccak256))
+                .await
+                .unwrap();
+
+            // Closing the connection stops the host.
+            client.shutdown().unwrap();
+            data
+        })
+        .await
+        .unwrap();
+
+        let hints = incoming_hints.lock().await.clone();
+        (hints, data)
+    }
+
+    #[test]
+    fn test_transport_addr_roundtrip() {
+        for s in ["unix:/tmp/kona.sock", "tcp:127.0.0.1:7300", "tcp:[::1]:7300"] {
+            assert_eq!(TransportAddr::from_str(s).unwrap().to_string(), s);
+        }
+        assert!(TransportAddr::from_str("unix:").is_err());
+        assert!(TransportAddr::from_str("tcp:localhost").is_err());
+        assert!(TransportAddr::from_str("/tmp/kona.sock").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_unix_transport() {
+        let (client, host) = UnixStream::pair().unwrap();
+        let (hints, data) =
+            run_session(StreamTransport::new(client).unwrap(), StreamTransport::new(host).unwrap())
+                .await;
+
+        assert_eq!(hints, [MOCK_HINT]);
+        assert_eq!(data, MOCK_DATA);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tcp_transport() {
+        let listener = TransportListener::bind(&"tcp:127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let accept = std::thread::spawn(move || listener.accept().unwrap().0);
+        let client = connect(&addr).unwrap();
+        let host = accept.join().unwrap();
+
+        let (hints, data) = run_session(client, host).await;
+        assert_eq!(hints, [MOCK_HINT]);
+        assert_eq!(data, MOCK_DATA);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_transport_splits_large_writes() {
+        let (client, host) = UnixStream::pair().unwrap();
+        let client = StreamTransport::new(client).unwrap();
+        let host = StreamTransport::new(host).unwrap();
+
+        let data = (0..MAX_FRAME_SIZE * 2 + 1).map(|i| i as u8).collect::<Vec<_>>();
+        let expected = data.clone();
+        let reader = tokio::task::spawn(async move {
+            let mut buf = vec![0u8; expected.len()];
+            host.preimage_channel().read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, expected);
+        });
+        client.preimage_channel().write(&data).await.unwrap();
+        reader.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_transport_reads_do_not_block_executor() {
+        let (client, host) = UnixStream::pair().unwrap();
+        let client = StreamTransport::new(client).unwrap();
+        let host = StreamTransport::new(host).unwrap();
+
+        // The pending read yields to the single-threaded executor, so that the write that
+        // completes it can run.
+        let reader = tokio::task::spawn(async move {
+            let mut buf = [0u8; 4];
+            host.hint_channel().read_exact(&mut buf).await.unwrap();
+            buf
+        });
+        tokio::task::yield_now().await;
+        client.hint_channel().write(b"ping").await.unwrap();
+        assert_eq!(&reader.await.unwrap(), b"ping");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_transport_rejects_oversized_frames() {
+        let (mut client, host) = UnixStream::pair().unwrap();
+        let host = StreamTransport::new(host).unwrap();
+
+        // A frame header announcing a payload larger than the maximum closes the connection,
+        // without waiting for the payload.
+        let mut header = vec![1u8];
+        header.extend_from_slice(&(MAX_FRAME_SIZE as u32 + 1).to_be_bytes());
+        client.write_all(&header).unwrap();
+
+        let mut buf = [0u8; 1];
+        assert!(host.preimage_channel().read_exact(&mut buf).await.is_err());
+        assert_eq!(client.read(&mut buf).unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_transport_shutdown_closes_channels() {
+        let (client, host) = UnixStream::pair().unwrap();
+        let client = StreamTransport::new(client).unwrap();
+        let host = StreamTransport::new(host).unwrap();
+
+        host.shutdown().unwrap();
+        let oracle_reader = OracleReader::new(client.preimage_channel());
+        assert!(oracle_reader
+            .get(PreimageKey::new(*keccak256(MOCK_DATA), PreimageKeyType::Keccak256))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! This module contains the [StreamTransport], which multiplexes the hint and preimage channels
+//! over a single connected byte stream.
+//!
+//! **Framing** (all integers are big-endian):
+//! ```text
+//! frame   = channel ++ length ++ payload
+//! channel = u8 (0 = hint, 1 = preimage)
+//! length  = u32 (at most MAX_FRAME_SIZE)
+//! payload = length bytes
+//! ```
+//! Every [Channel::write] is sent as a single frame, or split into several frames if it is larger
+//! than [MAX_FRAME_SIZE]. Incoming frames are demultiplexed onto their channel by a dedicated
+//! reader thread, so that a read on one channel never holds up the other. Reads await the frames
+//! of their channel, and never block the thread that polls them. A frame that
+//! is addressed to an unknown channel or exceeds [MAX_FRAME_SIZE] closes the connection.
+
+use crate::Channel;
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    lock::Mutex as AsyncMutex,
+    StreamExt,
+};
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::Mutex,
+    thread,
+};
+use tracing::warn;
+
+/// The identifier of the hint channel within a frame.
+const HINT_CHANNEL_ID: u8 = 0;
+
+/// The identifier of the preimage channel within a frame.
+const PREIMAGE_CHANNEL_ID: u8 = 1;
+
+/// The size of a frame header, in bytes.
+const FRAME_HEADER_SIZE: usize = 1 + 4;
+
+/// The maximum size of a frame's payload, in bytes. Bounds the memory that a peer can make the
+/// reader allocate for a single frame.
+pub const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// A connected, bidirectional byte stream that a [StreamTransport] can be established over.
+pub trait TransportStream: Read + Write + Send + Sync + Sized + 'static {
+    /// Creates a new handle to the same underlying stream.
+    fn try_clone(&self) -> io::Result<Self>;
+
+    /// Shuts down both halves of the stream.
+    fn shutdown(&self) -> io::Result<()>;
+}
+
+impl TransportStream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+#[cfg(unix)]
+impl TransportStream for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        std::os::unix::net::UnixStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+/// A [StreamTransport] carries both the hint and preimage channels over a single
+/// [TransportStream], such as a Unix domain socket or a TCP connection.
+pub struct StreamTransport {
+    /// The hint channel.
+    hint: StreamChannel,
+    /// The preimage channel.
+    preimage: StreamChannel,
+    /// Shuts down the underlying stream.
+    shutdown: Arc<dyn Fn() -> io::Result<()> + Send + Sync>,
+}
+
+impl Debug for StreamTransport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StreamTransport")
+            .field("hint", &self.hint)
+            .field("preimage", &self.preimage)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StreamTransport {
+    /// Create a new [StreamTransport] over the given stream, and start demultiplexing its incoming
+    /// frames.
+    pub fn new<S: TransportStream>(stream: S) -> Result<Self> {
+        let reader = stream.try_clone().map_err(|e| anyhow!("Failed to clone stream: {e}"))?;
+        let writer = stream.try_clone().map_err(|e| anyhow!("Failed to clone stream: {e}"))?;
+
+        let (hint_tx, hint_rx) = mpsc::unbounded();
+        let (preimage_tx, preimage_rx) = mpsc::unbounded();
+        thread::Builder::new()
+            .name("preimage-transport".into())
+            .spawn(move || demultiplex(reader, hint_tx, preimage_tx))
+            .map_err(|e| anyhow!("Failed to spawn transport reader: {e}"))?;
+
+        let writer: Arc<Mutex<Box<dyn Write + Send>>> = Arc::new(Mutex::new(Box::new(writer)));
+        Ok(Self {
+            hint: StreamChannel::new(HINT_CHANNEL_ID, writer.clone(), hint_rx),
+            preimage: StreamChannel::new(PREIMAGE_CHANNEL_ID, writer, preimage_rx),
+            shutdown: Arc::new(move || stream.shutdown()),
+        })
+    }
+
+    /// Returns the [StreamChannel] that hints are exchanged over.
+    pub fn hint_channel(&self) -> StreamChannel {
+        self.hint.clone()
+    }
+
+    /// Returns the [StreamChannel] that preimage requests are exchanged over.
+    pub fn preimage_channel(&self) -> StreamChannel {
+        self.preimage.clone()
+    }
+
+    /// Shuts down the underlying stream. Any pending or future reads on either channel return once
+    /// the stream has been shut down.
+    pub fn shutdown(&self) -> Result<()> {
+        (self.shutdown)().map_err(|e| anyhow!("Failed to shut down stream: {e}"))
+    }
+}
+
+/// One of the two [Channel]s multiplexed over a [StreamTransport].
+#[derive(Clone)]
+pub struct StreamChannel {
+    /// The identifier of the channel within a frame.
+    id: u8,
+    /// The write half of the stream, shared with the other channel.
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    /// The frames received for this channel.
+    receiver: Arc<AsyncMutex<FrameReceiver>>,
+}
+
+impl Debug for StreamChannel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StreamChannel").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+impl StreamChannel {
+    /// Create a new [StreamChannel] with the given identifier.
+    fn new(
+        id: u8,
+        writer: Arc<Mutex<Box<dyn Write + Send>>>,
+        frames: UnboundedReceiver<Vec<u8>>,
+    ) -> Self {
+        let receiver = FrameReceiver { frames, pending: Vec::new(), cursor: 0 };
+        Self { id, writer, receiver: Arc::new(AsyncMutex::new(receiver)) }
+    }
+}
+
+#[async_trait]
+impl Channel for StreamChannel {
+    async fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        Ok(self.receiver.lock().await.read(buf).await)
+    }
+
+    async fn read_exact(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut receiver = self.receiver.lock().await;
+
+        let mut read = 0;
+        while read < buf.len() {
+            let chunk_read = receiver.read(&mut buf[read..]).await;
+            if chunk_read == 0 {
+                bail!("Channel closed after reading {} of {} bytes", read, buf.len());
+            }
+            read += chunk_read;
+        }
+
+        Ok(read)
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize> {
+        let mut writer = self.writer.lock().map_err(|_| anyhow!("Channel lock poisoned"))?;
+        for payload in buf.chunks(MAX_FRAME_SIZE) {
+            let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+            frame.push(self.id);
+            frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            frame.extend_from_slice(payload);
+
+            writer.write_all(&frame).map_err(|e| anyhow!("Failed to write frame: {e}"))?;
+        }
+        writer.flush().map_err(|e| anyhow!("Failed to write frame: {e}"))?;
+
+        Ok(buf.len())
+    }
+}
+
+/// Buffers the payloads of the frames received for a single channel.
+struct FrameReceiver {
+    /// The payloads of received frames, in order.
+    frames: UnboundedReceiver<Vec<u8>>,
+    /// The payload that is currently being read.
+    pending: Vec<u8>,
+    /// The number of bytes of `pending` that have already been read.
+    cursor: usize,
+}
+
+impl FrameReceiver {
+    /// Reads buffered bytes into `buf`, waiting for a frame to arrive if none are buffered.
+    /// Returns `0` once the stream has been closed.
+    async fn read(&mut self, buf: &mut [u8]) -> usize {
+        while self.cursor == self.pending.len() {
+            match self.frames.next().await {
+                Some(frame) => {
+                    self.pending = frame;
+                    self.cursor = 0;
+                }
+                None => return 0,
+            }
+        }
+
+        let n = buf.len().min(self.pending.len() - self.cursor);
+        buf[..n].copy_from_slice(&self.pending[self.cursor..self.cursor + n]);
+        self.cursor += n;
+        n
+    }
+}
+
+/// Reads frames from the stream and forwards their payloads to the channel they are addressed to,
+/// until the stream is closed. Dropping the senders on exit signals the closure to both channels.
+/// Malformed frames shut down the stream.
+fn demultiplex<S: TransportStream>(
+    mut stream: S,
+    hint_tx: UnboundedSender<Vec<u8>>,
+    preimage_tx: UnboundedSender<Vec<u8>>,
+) {
+    loop {
+        let mut header = [0u8; FRAME_HEADER_SIZE];
+        if stream.read_exact(&mut header).is_err() {
+            break;
+        }
+
+        let tx = match header[0] {
+            HINT_CHANNEL_ID => &hint_tx,
+            PREIMAGE_CHANNEL_ID => &preimage_tx,
+            id => {
+                warn!(target: "transport", "Received frame for unknown channel {id}, closing");
+                let _ = stream.shutdown();
+                break;
+            }
+        };
+
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if length > MAX_FRAME_SIZE {
+            warn!(
+                target: "transport",
+                "Received frame of {length} bytes, exceeding the maximum of {MAX_FRAME_SIZE}, closing"
+            );
+            let _ = stream.shutdown();
+            break;
+        }
+
+        let mut payload = vec![0u8; length];
+        if stream.read_exact(&mut payload).is_err() {
+            break;
+        }
+
+        // The receiving channel may already have been dropped, in which case the frame is
+        // discarded.
+        let _ = tx.unbounded_send(payload);
+    }
+}
@@ -0,0 +1,565 @@
+//! This module contains the [Hint] type, the typed form of the hints that the client program sends
+//! to the host, and the [HintType] enum.
+//!
+//! **Encoding**
+//! ```text
+//! hint    = name ++ ["/v" ++ version] ++ " " ++ hex(data)
+//! ```
+//! The layout of `data` is determined by the hint type and version. Hints are encoded without a
+//! version suffix when they use [HINT_VERSION_0], which keeps them compatible with hosts that are
+//! unaware of versioning.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_primitives::{hex, Address, Bytes, B256};
+use core::fmt::Display;
+
+/// The initial version of the hint data encoding.
+pub const HINT_VERSION_0: u8 = 0;
+
+/// The [HintType] enum is used to specify the type of hint that was received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HintType {
+    /// A hint that specifies the block header of a layer 1 block.
+    L1BlockHeader,
+    /// A hint that specifies the transactions of a layer 1 block.
+    L1Transactions,
+    /// A hint that specifies the state node of a layer 1 block.
+    L1Receipts,
+    /// A hint that specifies a blob in the layer 1 beacon chain.
+    L1Blob,
+    /// A hint that specifies a precompile call on layer 1.
+    L1Precompile,
+    /// A hint that specifies the state node in the L1 state trie.
+    L1StateNode,
+    /// A hint that specifies the proof on the path to an account in the L1 state trie.
+    L1AccountProof,
+    /// A hint that specifies the proof on the path to a storage slot in an account within the L1
+    /// state trie.
+    L1AccountStorageProof,
+    /// A hint that specifies the code of a contract on layer 1.
+    L1Code,
+    /// A hint that specifies the block header of a layer 2 block.
+    L2BlockHeader,
+    /// A hint that specifies the transactions of a layer 2 block.
+    L2Transactions,
+    /// A hint that specifies the code of a contract on layer 2.
+    L2Code,
+    /// A hint that specifies the preimage of the starting L2 output root on layer 2.
+    StartingL2Output,
+    /// A hint that specifies the state node in the L2 state trie.
+    L2StateNode,
+    /// A hint that specifies the proof on the path to an account in the L2 state trie.
+    L2AccountProof,
+    /// A hint that specifies the proof on the path to a storage slot in an account within in the
+    /// L2 state trie.
+    L2AccountStorageProof,
+}
+
+impl TryFrom<&str> for HintType {
+    type Error = HintError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "l1-block-header" => Ok(HintType::L1BlockHeader),
+            "l1-transactions" => Ok(HintType::L1Transactions),
+            "l1-receipts" => Ok(HintType::L1Receipts),
+            "l1-blob" => Ok(HintType::L1Blob),
+            "l1-precompile" => Ok(HintType::L1Precompile),
+            "l1-state-node" => Ok(HintType::L1StateNode),
+            "l1-account-proof" => Ok(HintType::L1AccountProof),
+            "l1-account-storage-proof" => Ok(HintType::L1AccountStorageProof),
+            "l1-code" => Ok(HintType::L1Code),
+            "l2-block-header" => Ok(HintType::L2BlockHeader),
+            "l2-transactions" => Ok(HintType::L2Transactions),
+            "l2-code" => Ok(HintType::L2Code),
+            "starting-l2-output" => Ok(HintType::StartingL2Output),
+            "l2-state-node" => Ok(HintType::L2StateNode),
+            "l2-account-proof" => Ok(HintType::L2AccountProof),
+            "l2-account-storage-proof" => Ok(HintType::L2AccountStorageProof),
+            _ => Err(HintError::UnknownType(value.to_string())),
+        }
+    }
+}
+
+impl From<HintType> for &str {
+    fn from(value: HintType) -> Self {
+        match value {
+            HintType::L1BlockHeader => "l1-block-header",
+            HintType::L1Transactions => "l1-transactions",
+            HintType::L1Receipts => "l1-receipts",
+            HintType::L1Blob => "l1-blob",
+            HintType::L1Precompile => "l1-precompile",
+            HintType::L1StateNode => "l1-state-node",
+            HintType::L1AccountProof => "l1-account-proof",
+            HintType::L1AccountStorageProof => "l1-account-storage-proof",
+            HintType::L1Code => "l1-code",
+            HintType::L2BlockHeader => "l2-block-header",
+            HintType::L2Transactions => "l2-transactions",
+            HintType::L2Code => "l2-code",
+            HintType::StartingL2Output => "starting-l2-output",
+            HintType::L2StateNode => "l2-state-node",
+            HintType::L2AccountProof => "l2-account-proof",
+            HintType::L2AccountStorageProof => "l2-account-storage-proof",
+        }
+    }
+}
+
+impl Display for HintType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s: &str = (*self).into();
+        write!(f, "{}", s)
+    }
+}
+
+/// A hint sent by the client program to the host, with its data decoded into structured fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    /// Requests the header of the L1 block with the given hash.
+    L1BlockHeader {
+        /// The hash of the block.
+        hash: B256,
+    },
+    /// Requests the transactions of the L1 block with the given hash.
+    L1Transactions {
+        /// The hash of the block.
+        hash: B256,
+    },
+    /// Requests the receipts of the L1 block with the given hash.
+    L1Receipts {
+        /// The hash of the block.
+        hash: B256,
+    },
+    /// Requests a blob, along with its commitment and proof.
+    L1Blob {
+        /// The versioned hash of the blob.
+        hash: B256,
+        /// The index of the blob within its block.
+        index: u64,
+        /// The timestamp of the block that the blob was included in.
+        timestamp: u64,
+    },
+    /// Requests the result of a precompile call.
+    L1Precompile {
+        /// The address of the precompile.
+        address: Address,
+        /// The input to the precompile.
+        input: Bytes,
+    },
+    /// Requests the node of the L1 state trie with the given hash.
+    L1StateNode {
+        /// The hash of the trie node.
+        hash: B256,
+    },
+    /// Requests the proof of an account in the L1 state trie.
+    L1AccountProof {
+        /// The number of the L1 block whose state the proof is against.
+        block_number: u64,
+        /// The address of the account.
+        address: Address,
+    },
+    /// Requests the proof of a storage slot of an account in the L1 state trie.
+    L1AccountStorageProof {
+        /// The number of the L1 block whose state the proof is against.
+        block_number: u64,
+        /// The address of the account.
+        address: Address,
+        /// The storage slot.
+        slot: B256,
+    },
+    /// Requests the L1 bytecode with the given code hash.
+    L1Code {
+        /// The code hash.
+        hash: B256,
+    },
+    /// Requests the header of the L2 block with the given hash.
+    L2BlockHeader {
+        /// The hash of the block.
+        hash: B256,
+    },
+    /// Requests the transactions of the L2 block with the given hash.
+    L2Transactions {
+        /// The hash of the block.
+        hash: B256,
+    },
+    /// Requests the bytecode with the given code hash.
+    L2Code {
+        /// The code hash.
+        hash: B256,
+    },
+    /// Requests the preimage of the starting L2 output root.
+    StartingL2Output {
+        /// The starting L2 output root.
+        output_root: B256,
+    },
+    /// Requests the node of the L2 state trie with the given hash.
+    L2StateNode {
+        /// The hash of the trie node.
+        hash: B256,
+    },
+    /// Requests the proof of an account in the L2 state trie.
+    L2AccountProof {
+        /// The number of the L2 block whose state the proof is against.
+        block_number: u64,
+        /// The address of the account.
+        address: Address,
+    },
+    /// Requests the proof of a storage slot of an account in the L2 state trie.
+    L2AccountStorageProof {
+        /// The number of the L2 block whose state the proof is against.
+        block_number: u64,
+        /// The address of the account.
+        address: Address,
+        /// The storage slot.
+        slot: B256,
+    },
+}
+
+impl Hint {
+    /// Returns the [HintType] of the hint.
+    pub const fn hint_type(&self) -> HintType {
+        match self {
+            Self::L1BlockHeader { .. } => HintType::L1BlockHeader,
+            Self::L1Transactions { .. } => HintType::L1Transactions,
+            Self::L1Receipts { .. } => HintType::L1Receipts,
+            Self::L1Blob { .. } => HintType::L1Blob,
+            Self::L1Precompile { .. } => HintType::L1Precompile,
+            Self::L1StateNode { .. } => HintType::L1StateNode,
+            Self::L1AccountProof { .. } => HintType::L1AccountProof,
+            Self::L1AccountStorageProof { .. } => HintType::L1AccountStorageProof,
+            Self::L1Code { .. } => HintType::L1Code,
+            Self::L2BlockHeader { .. } => HintType::L2BlockHeader,
+            Self::L2Transactions { .. } => HintType::L2Transactions,
+            Self::L2Code { .. } => HintType::L2Code,
+            Self::StartingL2Output { .. } => HintType::StartingL2Output,
+            Self::L2StateNode { .. } => HintType::L2StateNode,
+            Self::L2AccountProof { .. } => HintType::L2AccountProof,
+            Self::L2AccountStorageProof { .. } => HintType::L2AccountStorageProof,
+        }
+    }
+
+    /// Encodes the data of the hint, without its type.
+    pub fn data(&self) -> Vec<u8> {
+        match self {
+            Self::L1BlockHeader { hash } |
+            Self::L1Transactions { hash } |
+            Self::L1Receipts { hash } |
+            Self::L2BlockHeader { hash } |
+            Self::L2Transactions { hash } |
+            Self::L1Code { hash } |
+            Self::L2Code { hash } |
+            Self::L1StateNode { hash } |
+            Self::L2StateNode { hash } |
+            Self::StartingL2Output { output_root: hash } => hash.to_vec(),
+            Self::L1Blob { hash, index, timestamp } => {
+                [hash.as_slice(), &index.to_be_bytes(), &timestamp.to_be_bytes()].concat()
+            }
+            Self::L1Precompile { address, input } => [address.as_slice(), input.as_ref()].concat(),
+            Self::L1AccountProof { block_number, address } |
+            Self::L2AccountProof { block_number, address } => {
+                [block_number.to_be_bytes().as_ref(), address.as_slice()].concat()
+            }
+            Self::L1AccountStorageProof { block_number, address, slot } |
+            Self::L2AccountStorageProof { block_number, address, slot } => {
+                [block_number.to_be_bytes().as_ref(), address.as_slice(), slot.as_slice()].concat()
+            }
+        }
+    }
+
+    /// Encodes the hint into the string that is written to the host.
+    pub fn encode(&self) -> String {
+        alloc::format!("{} {}", self.hint_type(), hex::encode(self.data()))
+    }
+
+    /// Decodes a hint from the string that was written by the client program.
+    pub fn decode(s: &str) -> Result<Self, HintError> {
+        let (name, version, data) = Self::split(s)?;
+        let hint_type = HintType::try_from(name)?;
+        if version != HINT_VERSION_0 {
+            return Err(HintError::UnsupportedVersion(hint_type, version));
+        }
+
+        let data = hex::decode(data).map_err(|e| HintError::InvalidHex(hint_type, e))?;
+        Self::decode_data(hint_type, &data)
+    }
+
+    /// Splits a hint into its name, the version of its data encoding and its hex encoded data. The
+    /// version suffix is stripped from the name, and defaults to [HINT_VERSION_0] if absent. The
+    /// name does not need to be a known [HintType], so that custom hints can be split as well.
+    pub fn split(s: &str) -> Result<(&str, u8, &str), HintError> {
+        let (name, data) = s.split_once(' ').ok_or_else(|| HintError::InvalidFormat(s.into()))?;
+        if data.contains(' ') {
+            return Err(HintError::InvalidFormat(s.into()));
+        }
+
+        match name.split_once("/v") {
+            Some((name, version)) => {
+                let version =
+                    version.parse::<u8>().map_err(|_| HintError::InvalidFormat(s.into()))?;
+                Ok((name, version, data))
+            }
+            None => Ok((name, HINT_VERSION_0, data)),
+        }
+    }
+
+    /// Decodes the data of a hint of the given [HintType].
+    pub fn decode_data(hint_type: HintType, data: &[u8]) -> Result<Self, HintError> {
+        match hint_type {
+            HintType::L1BlockHeader => {
+                Ok(Self::L1BlockHeader { hash: read_hash(hint_type, data)? })
+            }
+            HintType::L1Transactions => {
+                Ok(Self::L1Transactions { hash: read_hash(hint_type, data)? })
+            }
+            HintType::L1Receipts => Ok(Self::L1Receipts { hash: read_hash(hint_type, data)? }),
+            HintType::L2BlockHeader => {
+                Ok(Self::L2BlockHeader { hash: read_hash(hint_type, data)? })
+            }
+            HintType::L2Transactions => {
+                Ok(Self::L2Transactions { hash: read_hash(hint_type, data)? })
+            }
+            HintType::L1Code => Ok(Self::L1Code { hash: read_hash(hint_type, data)? }),
+            HintType::L2Code => Ok(Self::L2Code { hash: read_hash(hint_type, data)? }),
+            HintType::StartingL2Output => {
+                Ok(Self::StartingL2Output { output_root: read_hash(hint_type, data)? })
+            }
+            HintType::L1StateNode => Ok(Self::L1StateNode { hash: read_hash(hint_type, data)? }),
+            HintType::L2StateNode => Ok(Self::L2StateNode { hash: read_hash(hint_type, data)? }),
+            HintType::L1Blob => {
+                expect_len(hint_type, data, 32 + 8 + 8)?;
+                Ok(Self::L1Blob {
+                    hash: B256::from_slice(&data[..32]),
+                    index: read_u64(&data[32..40]),
+                    timestamp: read_u64(&data[40..48]),
+                })
+            }
+            HintType::L1Precompile => {
+                if data.len() < 20 {
+                    return Err(HintError::TooShort(hint_type, 20, data.len()));
+                }
+                Ok(Self::L1Precompile {
+                    address: Address::from_slice(&data[..20]),
+                    input: Bytes::copy_from_slice(&data[20..]),
+                })
+            }
+            HintType::L1AccountProof => {
+                let (block_number, address) = read_account(hint_type, data)?;
+                Ok(Self::L1AccountProof { block_number, address })
+            }
+            HintType::L1AccountStorageProof => {
+                let (block_number, address, slot) = read_storage_slot(hint_type, data)?;
+                Ok(Self::L1AccountStorageProof { block_number, address, slot })
+            }
+            HintType::L2AccountProof => {
+                let (block_number, address) = read_account(hint_type, data)?;
+                Ok(Self::L2AccountProof { block_number, address })
+            }
+            HintType::L2AccountStorageProof => {
+                let (block_number, address, slot) = read_storage_slot(hint_type, data)?;
+                Ok(Self::L2AccountStorageProof { block_number, address, slot })
+            }
+        }
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+/// An error that occurred while decoding a [Hint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintError {
+    /// The hint is not of the form `<name> <hex data>`.
+    InvalidFormat(String),
+    /// The hint type is not known.
+    UnknownType(String),
+    /// The version of the hint data encoding is not supported.
+    UnsupportedVersion(HintType, u8),
+    /// The hint data is not valid hex.
+    InvalidHex(HintType, hex::FromHexError),
+    /// The hint data does not have the length required by its type.
+    /// Expected: [usize], Actual: [usize].
+    InvalidLength(HintType, usize, usize),
+    /// The hint data is shorter than the minimum length required by its type.
+    /// Minimum: [usize], Actual: [usize].
+    TooShort(HintType, usize, usize),
+}
+
+impl Display for HintError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidFormat(hint) => write!(f, "Invalid hint format: \"{hint}\""),
+            Self::UnknownType(name) => write!(f, "Unknown hint type: {name}"),
+            Self::UnsupportedVersion(hint_type, version) => {
+                write!(f, "Unsupported version {version} of {hint_type} hint")
+            }
+            Self::InvalidHex(hint_type, e) => {
+                write!(f, "Invalid hex data in {hint_type} hint: {e}")
+            }
+            Self::InvalidLength(hint_type, expected, actual) => write!(
+                f,
+                "Invalid {hint_type} hint data length: expected {expected} bytes, got {actual}"
+            ),
+            Self::TooShort(hint_type, min, actual) => write!(
+                f,
+                "Invalid {hint_type} hint data length: expected at least {min} bytes, got {actual}"
+            ),
+        }
+    }
+}
+
+/// Ensures that the hint data has exactly the given length.
+fn expect_len(hint_type: HintType, data: &[u8], len: usize) -> Result<(), HintError> {
+    if data.len() != len {
+        return Err(HintError::InvalidLength(hint_type, len, data.len()));
+    }
+    Ok(())
+}
+
+/// Reads hint data that consists of a single 32-byte hash.
+fn read_hash(hint_type: HintType, data: &[u8]) -> Result<B256, HintError> {
+    expect_len(hint_type, data, 32)?;
+    Ok(B256::from_slice(data))
+}
+
+/// Reads hint data that consists of a block number and an account address.
+fn read_account(hint_type: HintType, data: &[u8]) -> Result<(u64, Address), HintError> {
+    expect_len(hint_type, data, 8 + 20)?;
+    Ok((read_u64(&data[..8]), Address::from_slice(&data[8..28])))
+}
+
+/// Reads hint data that consists of a block number, an account address and a storage slot.
+fn read_storage_slot(hint_type: HintType, data: &[u8]) -> Result<(u64, Address, B256), HintError> {
+    expect_len(hint_type, data, 8 + 20 + 32)?;
+    Ok((read_u64(&data[..8]), Address::from_slice(&data[8..28]), B256::from_slice(&data[28..60])))
+}
+
+/// Reads a big-endian u64 from an 8-byte slice.
+fn read_u64(data: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(data);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::{address, b256, bytes};
+
+    fn all_hints() -> Vec<Hint> {
+        let hash = b256!("0101010101010101010101010101010101010101010101010101010101010101");
+        let address = address!("4200000000000000000000000000000000000016");
+        vec![
+            Hint::L1BlockHeader { hash },
+            Hint::L1Transactions { hash },
+            Hint::L1Receipts { hash },
+            Hint::L1Blob { hash, index: 3, timestamp: 1_700_000_000 },
+            Hint::L1Precompile { address, input: bytes!("deadbeef") },
+            Hint::L1Precompile { address, input: Bytes::new() },
+            Hint::L1StateNode { hash },
+            Hint::L1AccountProof { block_number: 42, address },
+            Hint::L1AccountStorageProof { block_number: 42, address, slot: hash },
+            Hint::L1Code { hash },
+            Hint::L2BlockHeader { hash },
+            Hint::L2Transactions { hash },
+            Hint::L2Code { hash },
+            Hint::StartingL2Output { output_root: hash },
+            Hint::L2StateNode { hash },
+            Hint::L2AccountProof { block_number: 42, address },
+            Hint::L2AccountStorageProof { block_number: 42, address, slot: hash },
+        ]
+    }
+
+    #[test]
+    fn test_hint_roundtrip() {
+        for hint in all_hints() {
+            let encoded = hint.encode();
+            assert!(encoded.starts_with(&alloc::format!("{} ", hint.hint_type())));
+            assert_eq!(Hint::decode(&encoded).unwrap(), hint);
+
+            // The version suffix is stripped when the hint is split.
+            let data = hex::encode(hint.data());
+            let versioned = alloc::format!("{}/v0 {data}", hint.hint_type());
+            let name: &str = hint.hint_type().into();
+            assert_eq!(Hint::split(&versioned).unwrap(), (name, HINT_VERSION_0, data.as_str()));
+            assert_eq!(Hint::split(&encoded).unwrap(), (name, HINT_VERSION_0, data.as_str()));
+            assert_eq!(Hint::decode(&versioned).unwrap(), hint);
+            assert_eq!(Hint::decode_data(hint.hint_type(), &hint.data()).unwrap(), hint);
+        }
+    }
+
+    #[test]
+    fn test_hint_type_roundtrip() {
+        for hint in all_hints() {
+            let hint_type = hint.hint_type();
+            assert_eq!(HintType::try_from(hint_type.to_string().as_str()).unwrap(), hint_type);
+        }
+    }
+
+    #[test]
+    fn test_hint_encoding() {
+        let hint = Hint::L1Blob {
+            hash: b256!("0101010101010101010101010101010101010101010101010101010101010101"),
+            index: 1,
+            timestamp: 2,
+        };
+        assert_eq!(
+            hint.encode(),
+            "l1-blob 0101010101010101010101010101010101010101010101010101010101010101\
+             00000000000000010000000000000002"
+        );
+    }
+
+    #[test]
+    fn test_decode_versioned_hint() {
+        let hint = Hint::L2Code { hash: B256::repeat_byte(0xaa) };
+        let data = hex::encode(hint.data());
+
+        assert_eq!(Hint::decode(&alloc::format!("l2-code/v0 {data}")).unwrap(), hint);
+        assert_eq!(
+            Hint::decode(&alloc::format!("l2-code/v1 {data}")),
+            Err(HintError::UnsupportedVersion(HintType::L2Code, 1))
+        );
+
+        // Hints of custom types are split, but not decoded.
+        assert_eq!(Hint::split("custom/v1 01"), Ok(("custom", 1, "01")));
+        assert_eq!(Hint::decode("custom/v0 01"), Err(HintError::UnknownType("custom".into())));
+        assert_eq!(
+            Hint::split("custom/vx 01"),
+            Err(HintError::InvalidFormat("custom/vx 01".into()))
+        );
+    }
+
+    #[test]
+    fn test_decode_malformed_hints() {
+        assert_eq!(
+            Hint::decode("l1-block-header"),
+            Err(HintError::InvalidFormat("l1-block-header".into()))
+        );
+        assert_eq!(
+            Hint::decode("l1-block-header 00 00"),
+            Err(HintError::InvalidFormat("l1-block-header 00 00".into()))
+        );
+        assert_eq!(Hint::decode("l3-block 00"), Err(HintError::UnknownType("l3-block".into())));
+        assert!(matches!(
+            Hint::decode("l1-receipts zz"),
+            Err(HintError::InvalidHex(HintType::L1Receipts, _))
+        ));
+        assert_eq!(
+            Hint::decode("l1-blob 0000"),
+            Err(HintError::InvalidLength(HintType::L1Blob, 48, 2))
+        );
+        assert_eq!(
+            Hint::decode("l1-precompile 00"),
+            Err(HintError::TooShort(HintType::L1Precompile, 20, 1))
+        );
+        assert_eq!(
+            Hint::decode_data(HintType::L2AccountStorageProof, &[0u8; 28]),
+            Err(HintError::InvalidLength(HintType::L2AccountStorageProof, 60, 28))
+        );
+    }
+}
@@ -66,6 +66,15 @@ pub use sidecar::{
     VersionInformation, KZG_COMMITMENT_SIZE, KZG_PROOF_SIZE,
 };
 
+pub mod output_root;
+pub use output_root::{
+    OutputRoot, OutputRootV0, L2_TO_L1_MESSAGE_PASSER_ADDRESS, OUTPUT_ROOT_V0_LEN,
+    OUTPUT_ROOT_VERSION_0,
+};
+
+pub mod hint;
+pub use hint::{Hint, HintError, HintType, HINT_VERSION_0};
+
 pub mod frame;
 pub use frame::{Frame, DERIVATION_VERSION_0, FRAME_OVERHEAD, MAX_FRAME_LEN};
 
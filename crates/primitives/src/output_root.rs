@@ -0,0 +1,181 @@
+//! This module contains the [OutputRoot] type, the preimage of an L2 output root.
+
+use alloc::vec::Vec;
+use alloy_primitives::{address, keccak256, Address, B256};
+use anyhow::{bail, Result};
+
+/// The version of the [OutputRootV0] encoding.
+pub const OUTPUT_ROOT_VERSION_0: B256 = B256::with_last_byte(OutputRootV0::VERSION);
+
+/// The length of an encoded [OutputRootV0], in bytes.
+pub const OUTPUT_ROOT_V0_LEN: usize = 128;
+
+/// The address of the `L2ToL1MessagePasser` predeploy, whose storage root is committed to in the
+/// output root.
+pub const L2_TO_L1_MESSAGE_PASSER_ADDRESS: Address =
+    address!("4200000000000000000000000000000000000016");
+
+/// The preimage of an L2 output root. The first 32 bytes of the encoding are the version, which
+/// determines the layout of the remaining bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputRoot {
+    /// A version 0 output root.
+    V0(OutputRootV0),
+}
+
+impl OutputRoot {
+    /// Returns the version of the output root.
+    pub const fn version(&self) -> B256 {
+        match self {
+            Self::V0(_) => OUTPUT_ROOT_VERSION_0,
+        }
+    }
+
+    /// Returns the hash of the L2 block that the output root commits to.
+    pub const fn block_hash(&self) -> B256 {
+        match self {
+            Self::V0(output) => output.block_hash,
+        }
+    }
+
+    /// Encode the output root into its preimage.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::V0(output) => output.encode().to_vec(),
+        }
+    }
+
+    /// Decode an output root from its preimage.
+    pub fn decode(encoded: &[u8]) -> Result<Self> {
+        if encoded.len() < 32 {
+            bail!("Output root preimage too short to decode");
+        }
+
+        let version = B256::from_slice(&encoded[..32]);
+        match version {
+            OUTPUT_ROOT_VERSION_0 => Ok(Self::V0(OutputRootV0::decode(encoded)?)),
+            _ => bail!("Unsupported output root version: {version}"),
+        }
+    }
+
+    /// Computes the output root, the keccak256 hash of its preimage.
+    pub fn hash(&self) -> B256 {
+        match self {
+            Self::V0(output) => output.hash(),
+        }
+    }
+}
+
+impl From<OutputRootV0> for OutputRoot {
+    fn from(output: OutputRootV0) -> Self {
+        Self::V0(output)
+    }
+}
+
+/// A version 0 output root preimage.
+///
+/// *Encoding*
+/// output = `version ++ state_root ++ message_passer_storage_root ++ block_hash`
+/// * version                     = bytes32 (zero)
+/// * state_root                  = bytes32
+/// * message_passer_storage_root = bytes32
+/// * block_hash                  = bytes32
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct OutputRootV0 {
+    /// The state root of the L2 block.
+    pub state_root: B256,
+    /// The storage root of the `L2ToL1MessagePasser` predeploy at the L2 block.
+    pub message_passer_storage_root: B256,
+    /// The hash of the L2 block.
+    pub block_hash: B256,
+}
+
+impl OutputRootV0 {
+    /// The version number of the encoding, the last byte of [OUTPUT_ROOT_VERSION_0].
+    pub const VERSION: u8 = 0;
+
+    /// Create a new [OutputRootV0].
+    pub const fn new(
+        state_root: B256,
+        message_passer_storage_root: B256,
+        block_hash: B256,
+    ) -> Self {
+        Self { state_root, message_passer_storage_root, block_hash }
+    }
+
+    /// Encode the output root into its preimage.
+    pub fn encode(&self) -> [u8; OUTPUT_ROOT_V0_LEN] {
+        let mut encoded = [0u8; OUTPUT_ROOT_V0_LEN];
+        encoded[..32].copy_from_slice(OUTPUT_ROOT_VERSION_0.as_slice());
+        encoded[32..64].copy_from_slice(self.state_root.as_slice());
+        encoded[64..96].copy_from_slice(self.message_passer_storage_root.as_slice());
+        encoded[96..128].copy_from_slice(self.block_hash.as_slice());
+        encoded
+    }
+
+    /// Decode a version 0 output root from its preimage.
+    pub fn decode(encoded: &[u8]) -> Result<Self> {
+        if encoded.len() != OUTPUT_ROOT_V0_LEN {
+            bail!("Invalid output root preimage length: {}", encoded.len());
+        }
+        if encoded[..32] != OUTPUT_ROOT_VERSION_0 {
+            bail!("Output root preimage is not version 0");
+        }
+
+        Ok(Self {
+            state_root: B256::from_slice(&encoded[32..64]),
+            message_passer_storage_root: B256::from_slice(&encoded[64..96]),
+            block_hash: B256::from_slice(&encoded[96..128]),
+        })
+    }
+
+    /// Computes the output root, the keccak256 hash of its preimage.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.encode())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_output_root_roundtrip() {
+        let output = OutputRoot::V0(OutputRootV0::new(
+            B256::repeat_byte(0x01),
+            B256::repeat_byte(0x02),
+            B256::repeat_byte(0x03),
+        ));
+
+        let encoded = output.encode();
+        assert_eq!(encoded.len(), OUTPUT_ROOT_V0_LEN);
+        assert_eq!(OutputRoot::decode(&encoded).unwrap(), output);
+        assert_eq!(output.block_hash(), B256::repeat_byte(0x03));
+    }
+
+    #[test]
+    fn test_output_root_hash() {
+        let output = OutputRootV0::new(
+            B256::repeat_byte(0x01),
+            B256::repeat_byte(0x02),
+            B256::repeat_byte(0x03),
+        );
+
+        let mut preimage = [0u8; 128];
+        preimage[32..64].fill(0x01);
+        preimage[64..96].fill(0x02);
+        preimage[96..128].fill(0x03);
+        assert_eq!(output.hash(), keccak256(preimage));
+        assert_eq!(OutputRoot::from(output).hash(), output.hash());
+    }
+
+    #[test]
+    fn test_decode_output_root_invalid() {
+        assert!(OutputRoot::decode(&[0u8; 16]).is_err());
+        assert!(OutputRoot::decode(&[0u8; 96]).is_err());
+
+        let mut encoded = OutputRootV0::default().encode();
+        encoded[31] = 1;
+        assert!(OutputRoot::decode(&encoded).is_err());
+    }
+}